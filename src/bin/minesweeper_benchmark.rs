@@ -0,0 +1,65 @@
+use std::env;
+use std::time::Instant;
+
+use minesweeper_rust::core::board::{Board, FirstMove};
+use minesweeper_rust::core::controller::Strategy;
+use minesweeper_rust::core::game::{Difficulty, Game, Mode, Rules};
+use minesweeper_rust::core::turn::TurnOrder;
+use minesweeper_rust::core::validation::validate_mine_count;
+use minesweeper_rust::multiplayer::benchmark::{run, table};
+use minesweeper_rust::single_player::cli::{arg_value, Setup};
+
+const DEFAULT_GAMES: u32 = 1000;
+
+// Usage: minesweeper_benchmark [--games <n>] [--bots <list>] [--width <n>] [--height <n>]
+//                              [--difficulty <level> | --mines <n>] [--board Classic|SafeTile|NoGuess]
+//                              [--rules Classic|Elimination|PointsOnly] [--boards Shared|Parallel]
+//                              [--turn-order RoundRobin|Shuffled|Race] [--extra-turn yes|no] [--seed <n>]
+// Plays bots against each other and prints how each strategy did; it never reads input
+// Without --bots, every strategy plays; game i is played on seed + i
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    let setup = match Setup::from_args_with(&args, &["--games"]) {
+        Ok(setup) => setup,
+        Err(message) => {
+            eprintln!("{}", message);
+            return;
+        }
+    };
+    let num_games = match arg_value(&args, "--games").map(|value| value.trim().parse::<u32>()) {
+        None => DEFAULT_GAMES,
+        Some(Ok(num_games)) if num_games > 0 => num_games,
+        Some(_) => {
+            eprintln!("--games expects a positive number");
+            return;
+        }
+    };
+
+    let difficulty = setup.difficulty.unwrap_or(Difficulty::Easy);
+    let (h_size, v_size) = difficulty.board_size().unwrap_or((setup.h_size.unwrap_or(9), setup.v_size.unwrap_or(9)));
+    if validate_mine_count(h_size, v_size, difficulty).is_err() {
+        eprintln!("the mines must leave at least one safe tile");
+        return;
+    }
+    let strategies = if setup.bots.is_empty() { Strategy::ALL.to_vec() } else { setup.bots.clone() };
+    let first_seed = setup.seed.unwrap_or(0);
+
+    let rules = setup.rules.unwrap_or(Rules::PointsOnly);
+    let mode = setup.mode.unwrap_or(Mode::SharedBoard);
+    let new_game = |seed| {
+        Game::from_board(Board::new_deferred(h_size, v_size, difficulty, seed, setup.first_move.unwrap_or(FirstMove::SafeOpening)))
+            .with_rules(rules)
+            .with_mode(mode)
+            .with_turn_order(setup.turn_order.unwrap_or(TurnOrder::RoundRobin), setup.extra_turn.unwrap_or(false))
+    };
+
+    println!("{} games of {:?} on {}x{} {:?} boards, {:?} rules, seeds {} to {}",
+        num_games, strategies, h_size, v_size, difficulty, rules, first_seed, first_seed.wrapping_add(num_games as u64 - 1));
+
+    let started = Instant::now();
+    let tallies = run(new_game, &strategies, num_games, first_seed);
+
+    println!("{}", table(&tallies));
+    println!("took {:.1?}", started.elapsed());
+}
@@ -0,0 +1,24 @@
+use std::env;
+use std::io;
+
+use minesweeper_rust::multiplayer::client::join_game;
+use minesweeper_rust::single_player::cli::{arg_value, check_args, DEFAULT_ADDR};
+use minesweeper_rust::single_player::text_ui::get_name;
+
+// Usage: minesweeper_client [--addr <host:port>] [--name <name>]
+fn main() -> io::Result<()> {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    if let Err(message) = check_args(&args, &["--addr", "--name"], &[]) {
+        eprintln!("{}", message);
+        return Ok(());
+    }
+
+    let addr = arg_value(&args, "--addr").map(String::as_str).unwrap_or(DEFAULT_ADDR);
+    let name = match arg_value(&args, "--name") {
+        Some(name) => name.clone(),
+        None => get_name()?,
+    };
+
+    join_game(addr, &name)
+}
@@ -0,0 +1,52 @@
+use std::env;
+use std::io;
+use std::path::Path;
+
+use minesweeper_rust::core::board::{Board, FirstMove};
+use minesweeper_rust::core::debug;
+use minesweeper_rust::core::game::{Difficulty, Game, Mode, Rules};
+use minesweeper_rust::core::turn::TurnOrder;
+use minesweeper_rust::core::validation::validate_mine_count;
+use minesweeper_rust::multiplayer::server::host_game;
+use minesweeper_rust::single_player::cli::{arg_value, Setup, DEFAULT_ADDR};
+
+// Usage: minesweeper_server [--addr <host:port>] [--players <n>] [--width <n>] [--height <n>]
+//                           [--difficulty <level> | --mines <n>] [--board Classic|SafeTile|NoGuess]
+//                           [--rules Classic|Elimination|PointsOnly] [--turn-order RoundRobin|Shuffled|Race]
+//                           [--boards Shared|Parallel] [--extra-turn yes|no] [--seed <n>] [--debug <file>]
+// Unlike the game itself, the server never prompts: anything not given gets a default
+fn main() -> io::Result<()> {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    let setup = match Setup::from_args(&args) {
+        Ok(setup) => setup,
+        Err(message) => {
+            eprintln!("{}", message);
+            return Ok(());
+        }
+    };
+
+    // where the mines are, what the solver deduces and every move, kept out of the console
+    if let Some(path) = arg_value(&args, "--debug") {
+        debug::enable(Path::new(path))?;
+    }
+
+    let difficulty = setup.difficulty.unwrap_or(Difficulty::Easy);
+    let (h_size, v_size) = difficulty.board_size().unwrap_or((setup.h_size.unwrap_or(9), setup.v_size.unwrap_or(9)));
+    if validate_mine_count(h_size, v_size, difficulty).is_err() {
+        eprintln!("the mines must leave at least one safe tile");
+        return Ok(());
+    }
+    let seed = setup.seed.unwrap_or_else(rand::random);
+
+    let board = Board::new_deferred(h_size, v_size, difficulty, seed, setup.first_move.unwrap_or(FirstMove::SafeOpening));
+    let game = Game::from_board(board)
+        .with_rules(setup.rules.unwrap_or(Rules::PointsOnly))
+        .with_mode(setup.mode.unwrap_or(Mode::SharedBoard))
+        .with_turn_order(setup.turn_order.unwrap_or(TurnOrder::RoundRobin), setup.extra_turn.unwrap_or(false));
+
+    let addr = arg_value(&args, "--addr").map(String::as_str).unwrap_or(DEFAULT_ADDR);
+    host_game(addr, game, setup.num_players.unwrap_or(2) as usize)?;
+
+    Ok(())
+}
@@ -3,45 +3,70 @@
 use im::HashMap;
 use std::rc::Rc;
 use std::collections::HashSet;
+use serde::{Deserialize, Serialize};
 
 
 use crate::core::player::*;
+use crate::core::solver;
+use crate::core::debug;
+use crate::core::view::PlayerView;
 
 // TODO: remove dependency on single_player
 use crate::core::game::*;
 use crate::core::game::Difficulty;
 
+#[derive(Clone)]
 pub struct Board { 
     pub h_size: u32,  // horizontal size (grows to right)
     pub v_size: u32,  // vertical size (grows down)
-    board_map: HashMap<Coordinate, TileStatus>,
-    mine_coordinates: Rc<HashSet<Coordinate>> // Shared, immutable
+    pub seed: u64,    // same seed, size and difficulty => same mine layout
+    pub(crate) board_map: HashMap<Coordinate, TileStatus>,
+    pub(crate) mine_coordinates: Rc<HashSet<Coordinate>>, // Shared, immutable
+    pub(crate) pending_mines: Option<PendingMines>,       // Some until the first move of a deferred board
+    pub(crate) needs_guess: bool,                         // a no-guess board that fell back to a layout the solver cannot finish
 }
 
-// TODO: think about communication between server and players
-// Add a representation of board for players
+// What the first move of a deferred board is guaranteed to be safe from
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum FirstMove {
+    SafeTile,    // the chosen tile is never a mine
+    SafeOpening, // neither the chosen tile nor its neighbors are mines, so the first reveal opens an area
+    NoGuess,     // a safe opening from which the whole board can be solved without guessing
+}
+
+// How many layouts a no-guess board tries before falling back to a safe opening that may need a guess
+const MAX_NO_GUESS_ATTEMPTS: u64 = 1000;
+
+// Mines that are placed once the first coordinate is known
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub(crate) struct PendingMines {
+    pub(crate) num_mines: u32,
+    pub(crate) first_move: FirstMove,
+}
+
+// Players only ever get a PlayerView of the board (see view.rs)
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum TileStatus {
     Hidden,
     Flagged(PlayerId),
     Revealed(Tile)
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum Tile {
     Hint(i8),
     Mine
 }
 
-#[derive(Debug, Hash, Eq, PartialEq, Copy, Clone)]
+#[derive(Debug, Hash, Eq, PartialEq, Copy, Clone, Serialize, Deserialize)]
 pub struct Coordinate { pub x: u32, pub y: u32 }
 
 type Hint = i8;
 pub type BoardMap = HashMap<Coordinate, TileStatus>;
 
 impl Board {
-    pub fn new(h_size: u32, v_size: u32, difficulty: Difficulty) -> Board {
+    pub fn new(h_size: u32, v_size: u32, difficulty: Difficulty, seed: u64) -> Board {
         // initializes empty hashmap
         let board_map = Board::initialize_board_map(h_size, v_size);
 
@@ -49,8 +74,27 @@ impl Board {
         Board {
             h_size,
             v_size,
+            seed,
             board_map,
-            mine_coordinates: Rc::new(Board::random_mine_coordinates(h_size, v_size, difficulty)),
+            mine_coordinates: Rc::new(Board::random_mine_coordinates(h_size, v_size, difficulty, seed)),
+            pending_mines: None,
+            needs_guess: false,
+        }
+    }
+
+    // Creates a board whose mines are placed on the first move, away from the chosen tile
+    // The layout depends on the seed and on the first coordinate
+    pub fn new_deferred(h_size: u32, v_size: u32, difficulty: Difficulty, seed: u64, first_move: FirstMove) -> Board {
+        let board_map = Board::initialize_board_map(h_size, v_size);
+
+        Board {
+            h_size,
+            v_size,
+            seed,
+            board_map,
+            mine_coordinates: Rc::new(HashSet::new()),
+            pending_mines: Some(PendingMines { num_mines: Board::mine_count(h_size, v_size, difficulty), first_move }),
+            needs_guess: false,
         }
     }
     
@@ -60,10 +104,13 @@ impl Board {
         let board_map = Board::initialize_board_map(h_size, v_size);
 
         Board{
-            h_size: h_size,
-            v_size: v_size,
+            h_size,
+            v_size,
+            seed: 0,
             board_map, 
             mine_coordinates: Rc::new(mine_coordinates),
+            pending_mines: None,
+            needs_guess: false,
         }        
     }
 
@@ -80,7 +127,10 @@ impl Board {
     }
 
     pub fn num_mines(&self) -> u32 {
-        self.mine_coordinates.len() as u32
+        match &self.pending_mines {
+            Some(pending_mines) => pending_mines.num_mines,
+            None => self.mine_coordinates.len() as u32,
+        }
     }
 
     pub fn get_tile(&self, coordinate: &Coordinate) -> &TileStatus {
@@ -97,56 +147,135 @@ impl Board {
         self.board_map.iter()
     }
 
-    pub fn random_mine_coordinates(h_size: u32, v_size: u32, difficulty: Difficulty) -> HashSet<Coordinate> {
-        let mut random_coordinates: HashSet<Coordinate> = HashSet::new();
-        let board_size = (h_size * v_size) as f32; // To compare and multiply with floating point numbers
-
-        let num_mines: f32 = if board_size < 5.0 {
-            1.0
-        } else {
-            board_size * 
-                match difficulty {
-                    Difficulty::Easy => EASY,
-                    Difficulty::Medium => MEDIUM,
-                    Difficulty::Hard => HARD 
-                }
+    // Never covers the whole board; validate_mine_count tells the player when their choice does not fit
+    pub fn mine_count(h_size: u32, v_size: u32, difficulty: Difficulty) -> u32 {
+        let num_tiles = h_size * v_size;
+
+        let num_mines = match difficulty {
+            Difficulty::Easy => Board::mines_for_density(num_tiles, EASY),
+            Difficulty::Medium => Board::mines_for_density(num_tiles, MEDIUM),
+            Difficulty::Hard => Board::mines_for_density(num_tiles, HARD),
+            Difficulty::Beginner => BEGINNER_MINES,
+            Difficulty::Intermediate => INTERMEDIATE_MINES,
+            Difficulty::Expert => EXPERT_MINES,
+            Difficulty::Custom { mines } => mines,
+            Difficulty::Density(density) => Board::mines_for_density(num_tiles, density),
         };
 
-        let num_mines = num_mines.floor() as usize;
+        num_mines.min(num_tiles.saturating_sub(1))
+    }
 
-        use rand::Rng;
-        let mut rng = rand::thread_rng();
+    // Even a tiny board gets a mine
+    fn mines_for_density(num_tiles: u32, density: f32) -> u32 {
+        ((num_tiles as f32 * density).floor() as u32).max(1)
+    }
 
-        while random_coordinates.len() < num_mines as usize {
-            random_coordinates.insert( 
-                Coordinate {x: rng.gen_range(0..h_size), y: rng.gen_range(0..v_size)}
-            );
-        }
+    pub fn random_mine_coordinates(h_size: u32, v_size: u32, difficulty: Difficulty, seed: u64) -> HashSet<Coordinate> {
+        let num_mines = Board::mine_count(h_size, v_size, difficulty);
+        let random_coordinates = Board::random_mine_coordinates_excluding(h_size, v_size, num_mines, seed, &HashSet::new());
+        debug::log(|| format!("mines placed (seed {}): {}", seed, debug::coordinates(&random_coordinates)));
 
-        // For testing only!!
-        println!("mines are at: {:?}", random_coordinates);
+        random_coordinates
+    }
+
+    // Places `num_mines` mines anywhere but on the `excluded` coordinates
+    // A partial Fisher-Yates shuffle of the tile numbers: every layout is equally likely and it takes
+    // O(num_mines) steps however dense the board is. The shuffle only remembers the numbers it moved.
+    fn random_mine_coordinates_excluding(h_size: u32, v_size: u32, num_mines: u32, seed: u64, excluded: &HashSet<Coordinate>) -> HashSet<Coordinate> {
+        use rand::{Rng, SeedableRng};
+        // seeded instead of thread_rng so that a game can be reproduced
+        let mut rng = rand::rngs::StdRng::seed_from_u64(seed);
+
+        // tiles are numbered row by row, leaving out the excluded ones
+        let mut excluded_tiles: Vec<u32> = excluded.iter()
+            .filter(|coordinate| coordinate.x < h_size && coordinate.y < v_size)
+            .map(|coordinate| coordinate.y * h_size + coordinate.x)
+            .collect();
+        excluded_tiles.sort_unstable();
+        let num_candidates = h_size * v_size - excluded_tiles.len() as u32;
+
+        let mut moved: std::collections::HashMap<u32, u32> = std::collections::HashMap::new();
+        let mut random_coordinates: HashSet<Coordinate> = HashSet::new();
+
+        for i in 0..num_mines.min(num_candidates) {
+            let j = rng.gen_range(i..num_candidates);
+            let candidate = *moved.get(&j).unwrap_or(&j);
+            moved.insert(j, *moved.get(&i).unwrap_or(&i));
+
+            // the candidate-th tile that is not excluded
+            let tile = excluded_tiles.iter()
+                .fold(candidate, |tile, excluded_tile| if *excluded_tile <= tile { tile + 1 } else { tile });
+            random_coordinates.insert(Coordinate { x: tile % h_size, y: tile / h_size });
+        }
 
         random_coordinates
     }
 
+    // Places the mines of a deferred board so that the first move lands on a safe tile.
+    // If the board is too small to keep the whole opening free, only the tile itself is kept free.
+    fn place_pending_mines(&self, pending_mines: &PendingMines, first_coordinate: &Coordinate) -> Board {
+        let num_tiles = self.h_size * self.v_size;
+        let num_mines = pending_mines.num_mines.min(num_tiles - 1);
+
+        let safe_tile = HashSet::from([*first_coordinate]);
+        let excluded = match pending_mines.first_move {
+            FirstMove::SafeOpening | FirstMove::NoGuess => {
+                let mut opening = safe_tile.clone();
+                opening.extend(self.neighboring_coordinates(first_coordinate));
+
+                if num_tiles - opening.len() as u32 >= num_mines { opening } else { safe_tile }
+            },
+            FirstMove::SafeTile => safe_tile,
+        };
+
+        let with_mines = |seed: u64| Board {
+            h_size: self.h_size,
+            v_size: self.v_size,
+            seed: self.seed,
+            board_map: self.board_map.clone(),
+            mine_coordinates: Rc::new(Board::random_mine_coordinates_excluding(self.h_size, self.v_size, num_mines, seed, &excluded)),
+            pending_mines: None,
+            needs_guess: false,
+        };
+
+        let board = match pending_mines.first_move {
+            // rejects layouts the solver cannot finish; every attempt derives its seed from the board's seed
+            // so the chosen layout is still reproducible
+            FirstMove::NoGuess => (0..MAX_NO_GUESS_ATTEMPTS)
+                .map(|attempt| {
+                    debug::log(|| format!("no-guess layout, attempt {}", attempt + 1));
+                    with_mines(self.seed.wrapping_add(attempt))
+                })
+                .find(|board| solver::is_solvable(board, first_coordinate))
+                .unwrap_or_else(|| {
+                    debug::log(|| "no layout can be solved without guessing, falling back to a safe opening".to_string());
+                    Board { needs_guess: true, ..with_mines(self.seed) }
+                }),
+            _ => with_mines(self.seed),
+        };
+
+        debug::log(|| format!("mines placed on the first move at ({},{}) (seed {}):\n{}",
+            first_coordinate.x, first_coordinate.y, self.seed, debug::mine_map(&board)));
+
+        board
+    }
+
+    pub fn mines_placed(&self) -> bool {
+        self.pending_mines.is_none()
+    }
+
+    // True when a no-guess board found no layout the solver can finish and fell back to one that may need a guess
+    pub fn needs_guess(&self) -> bool {
+        self.needs_guess
+    }
+
     pub fn is_mine(&self, coordinate: &Coordinate) -> bool {
         self.mine_coordinates.contains(coordinate)
     }
 
     // Return type: Vec instead of HashSet for recursive `reveal_all`
-    fn neighboring_coordinates (&self, coordinate: &Coordinate) -> Vec<Coordinate> {
-        let relative_coordinates:[(i32, i32); 8] = [(-1, -1), (0, -1), (1, -1), (-1, 0), (1, 0), (-1, 1), (0, 1), (1, 1)];
-        let mut neighboring_coordinates = Vec::new();
-
-        for r_c in relative_coordinates {
-            let potential_coordinate = (coordinate.x as i32 + r_c.0 , coordinate.y as i32 + r_c.1 ); // u32 as i32 is ok
-            
-            if self.within_bounds(&potential_coordinate) {
-                neighboring_coordinates.push(Coordinate{x: potential_coordinate.0 as u32, y: potential_coordinate.1 as u32});
-            }   
-        }
-        
-        neighboring_coordinates
+    pub fn neighboring_coordinates (&self, coordinate: &Coordinate) -> Vec<Coordinate> {
+        neighboring_coordinates(self.h_size, self.v_size, coordinate)
     }
  
     pub fn get_hint(&self, coordinate: &Coordinate) -> Hint {
@@ -163,18 +292,62 @@ impl Board {
 
     // Updates Board using immutable hashmap
     pub fn update(&self, player_action: &PlayerAction) -> Board {
+        // a hint only looks, so it must not place the mines either
+        if player_action.action == Action::Hint {
+            return self.clone();
+        }
+
+        // the mines are placed on the first reveal, away from its tile; there is nothing to flag before that
+        if let Some(pending_mines) = &self.pending_mines {
+            return match player_action.action {
+                Action::Reveal | Action::Chord => self.place_pending_mines(pending_mines, &player_action.coordinate).update(player_action),
+                _ => self.clone(),
+            };
+        }
+
         let updated_board_map = match player_action.action {
             Action::Reveal => self.reveal(&player_action.coordinate, self.board_map.clone()),
             Action::Flag if self.is_mine(&player_action.coordinate) => self.board_map.update(player_action.coordinate, TileStatus::Flagged(player_action.player_id)),
             // flagging a non-mine reveals it 
-            _  => self.reveal(&player_action.coordinate, self.board_map.clone()) // penalty - 10 
+            Action::Flag => self.reveal(&player_action.coordinate, self.board_map.clone()), // penalty - 10 
+            Action::Unflag => self.unflag(&player_action.coordinate, self.board_map.clone()),
+            Action::Chord => self.chord(&player_action.coordinate, self.board_map.clone()),
+            Action::Hint => self.board_map.clone(),
         };
 
         Board {
             h_size: self.h_size,
             v_size: self.v_size,
+            seed: self.seed,
             board_map: updated_board_map,
             mine_coordinates: Rc::clone(&self.mine_coordinates),
+            pending_mines: None,
+            needs_guess: self.needs_guess,
+        }
+    }
+
+    // Chording only depends on what players can see
+    pub fn can_chord(&self, coordinate: &Coordinate) -> bool {
+        self.view().can_chord(coordinate)
+    }
+
+    fn chord(&self, coordinate: &Coordinate, board_map: BoardMap) -> BoardMap {
+        if !self.can_chord(coordinate) {
+            return board_map;
+        }
+
+        let hidden_neighbors = self.neighboring_coordinates(coordinate)
+            .into_iter()
+            .filter(|c| matches!(board_map.get(c), Some(TileStatus::Hidden)))
+            .collect();
+
+        self.reveal_all(hidden_neighbors, board_map)
+    }
+
+    fn unflag(&self, coordinate: &Coordinate, board_map: BoardMap) -> BoardMap {
+        match board_map.get(coordinate) {
+            Some(TileStatus::Flagged(_)) => board_map.update(*coordinate, TileStatus::Hidden),
+            _ => board_map
         }
     }
 
@@ -227,22 +400,33 @@ impl Board {
         }
     }    
 
+    // What players may see of this board: no mine locations
+    // Cheap, the view shares board_map with the board
+    pub fn view(&self) -> PlayerView {
+        PlayerView::new(self.h_size, self.v_size, self.num_mines(), self.board_map.clone())
+    }
+
     pub fn print(&self) {
-        for y in 0..self.v_size {
-            for x in 0..self.h_size {
-                match self.board_map.get(&Coordinate{ x, y }).unwrap() {
-                    TileStatus::Hidden => print!("?      "),
-                    TileStatus::Flagged(player_id) => print!("!,by {} ", player_id),
-                    TileStatus::Revealed(Tile::Hint(n)) => print!("{n}      "),
-                    TileStatus::Revealed(Tile::Mine) => print!("*      ")
-                }
-            }
-            println!();
-        }
-        println!();
+        self.view().print()
     }
 }
 
+// Neighbors of a coordinate on a board of the given size
+pub(crate) fn neighboring_coordinates(h_size: u32, v_size: u32, coordinate: &Coordinate) -> Vec<Coordinate> {
+    let relative_coordinates:[(i32, i32); 8] = [(-1, -1), (0, -1), (1, -1), (-1, 0), (1, 0), (-1, 1), (0, 1), (1, 1)];
+    let mut neighboring_coordinates = Vec::new();
+
+    for r_c in relative_coordinates {
+        let potential_coordinate = (coordinate.x as i32 + r_c.0 , coordinate.y as i32 + r_c.1 ); // u32 as i32 is ok
+        
+        if potential_coordinate.0 >= 0 && potential_coordinate.0 < h_size as i32 && potential_coordinate.1 >= 0 && potential_coordinate.1 < v_size as i32 {
+            neighboring_coordinates.push(Coordinate{x: potential_coordinate.0 as u32, y: potential_coordinate.1 as u32});
+        }   
+    }
+    
+    neighboring_coordinates
+}
+
 
 #[cfg(test)]
 mod tests {
@@ -251,21 +435,210 @@ mod tests {
     fn create_3x3() -> Board {
         let mine_coordinate = Coordinate{ x: 0, y: 0 };
         
-        return Board::new_test(3, 3, HashSet::from([mine_coordinate]))
+        Board::new_test(3, 3, HashSet::from([mine_coordinate]))
     }
 
     #[test]
     fn num_mine_easy() {
-        let new_board = Board::new(2, 2, Difficulty::Easy);
+        let new_board = Board::new(2, 2, Difficulty::Easy, 0);
         assert_eq!(new_board.mine_coordinates.len(), 1)
     }
 
     #[test]
     fn num_mines_hard() {
-        let new_board = Board::new(5, 5, Difficulty::Hard);
+        let new_board = Board::new(5, 5, Difficulty::Hard, 0);
         assert_eq!(new_board.mine_coordinates.len(), 5);
     }
 
+    // How often each tile gets a mine over many seeds
+    fn mine_frequencies(h_size: u32, v_size: u32, num_mines: u32, excluded: &HashSet<Coordinate>, num_layouts: u64) -> std::collections::HashMap<Coordinate, u32> {
+        let mut frequencies = std::collections::HashMap::new();
+
+        for seed in 0..num_layouts {
+            let mines = Board::random_mine_coordinates_excluding(h_size, v_size, num_mines, seed, excluded);
+            assert_eq!(mines.len(), num_mines as usize);
+
+            for coordinate in mines {
+                *frequencies.entry(coordinate).or_insert(0) += 1;
+            }
+        }
+
+        frequencies
+    }
+
+    #[test]
+    fn every_tile_equally_likely() {
+        // 5 of 25 tiles: each tile is a mine in a fifth of the layouts
+        let frequencies = mine_frequencies(5, 5, 5, &HashSet::new(), 20000);
+
+        assert_eq!(frequencies.len(), 25);
+        for count in frequencies.values() {
+            assert!((3700..=4300).contains(count), "{} is too far from 4000", count);
+        }
+    }
+
+    #[test]
+    fn excluded_tiles_stay_free() {
+        // a nearly full board: 14 mines on the 15 tiles left around an excluded corner
+        let excluded = HashSet::from([Coordinate{ x: 0, y: 0 }, Coordinate{ x: 1, y: 0 }, Coordinate{ x: 0, y: 1 }, Coordinate{ x: 1, y: 1 }]);
+        let frequencies = mine_frequencies(4, 5, 14, &excluded, 6000);
+
+        assert!(excluded.iter().all(|coordinate| !frequencies.contains_key(coordinate)));
+        assert_eq!(frequencies.len(), 16);
+        for count in frequencies.values() {
+            assert!((5100..=5400).contains(count), "{} is too far from 5250", count);
+        }
+    }
+
+    #[test]
+    fn every_layout_equally_likely() {
+        // 2 mines on 6 tiles make 15 layouts; a chi-squared test with 14 degrees of freedom
+        let num_layouts = 15000;
+        let mut counts: std::collections::HashMap<Vec<(u32, u32)>, u32> = std::collections::HashMap::new();
+
+        for seed in 0..num_layouts {
+            let mut layout: Vec<(u32, u32)> = Board::random_mine_coordinates_excluding(3, 2, 2, seed, &HashSet::new())
+                .into_iter()
+                .map(|coordinate| (coordinate.x, coordinate.y))
+                .collect();
+            layout.sort();
+            *counts.entry(layout).or_insert(0) += 1;
+        }
+
+        let expected = num_layouts as f64 / 15.0;
+        let chi_squared: f64 = counts.values().map(|count| (*count as f64 - expected).powi(2) / expected).sum();
+
+        assert_eq!(counts.len(), 15);
+        // the 99.9th percentile of the distribution
+        assert!(chi_squared < 36.12, "chi squared {} suggests the layouts are not uniform", chi_squared);
+    }
+
+    #[test]
+    fn dense_board_is_quick() {
+        let mines = Board::random_mine_coordinates_excluding(30, 30, 899, 1, &HashSet::new());
+
+        assert_eq!(mines.len(), 899);
+    }
+
+    #[test]
+    fn num_mines_presets() {
+        assert_eq!(Board::mine_count(9, 9, Difficulty::Beginner), 10);
+        assert_eq!(Board::mine_count(16, 16, Difficulty::Intermediate), 40);
+        assert_eq!(Board::mine_count(30, 16, Difficulty::Expert), 99);
+    }
+
+    #[test]
+    fn num_mines_custom_and_density() {
+        assert_eq!(Board::new(4, 4, Difficulty::Custom { mines: 7 }, 0).mine_coordinates.len(), 7);
+        assert_eq!(Board::mine_count(10, 10, Difficulty::Density(0.25)), 25);
+        assert_eq!(Board::mine_count(10, 10, Difficulty::Custom { mines: 0 }), 0);
+    }
+
+    #[test]
+    fn always_a_safe_tile() {
+        assert_eq!(Board::mine_count(1, 1, Difficulty::Easy), 0);
+        assert_eq!(Board::mine_count(1, 2, Difficulty::Hard), 1);
+        assert_eq!(Board::mine_count(3, 3, Difficulty::Custom { mines: 20 }), 8);
+        assert_eq!(Board::mine_count(5, 5, Difficulty::Expert), 24);
+    }
+
+    #[test]
+    fn same_seed_same_mines() {
+        let board = Board::new(10, 10, Difficulty::Hard, 42);
+        let same_board = Board::new(10, 10, Difficulty::Hard, 42);
+
+        assert_eq!(board.mine_coordinates, same_board.mine_coordinates);
+    }
+
+    #[test]
+    fn different_seed_different_mines() {
+        let board = Board::new(10, 10, Difficulty::Hard, 1);
+        let other_board = Board::new(10, 10, Difficulty::Hard, 2);
+
+        assert_ne!(board.mine_coordinates, other_board.mine_coordinates);
+    }
+
+    #[test]
+    fn deferred_board_has_no_mines_before_first_move() {
+        let board = Board::new_deferred(9, 9, Difficulty::Hard, 7, FirstMove::SafeOpening);
+
+        assert!(!board.mines_placed());
+        assert!(board.mine_coordinates.is_empty());
+        assert_eq!(board.num_mines(), Board::mine_count(9, 9, Difficulty::Hard));
+    }
+
+    #[test]
+    fn first_reveal_is_safe_tile() {
+        let first_coordinate = Coordinate{ x: 1, y: 1 };
+        let board = Board::new_deferred(5, 4, Difficulty::Hard, 7, FirstMove::SafeTile);
+
+        let updated_board = board.update(&PlayerAction{ player_id: 1, coordinate: first_coordinate, action: Action::Reveal });
+
+        assert!(updated_board.mines_placed());
+        assert!(!updated_board.is_mine(&first_coordinate));
+        assert_eq!(updated_board.num_mines(), board.num_mines());
+    }
+
+    #[test]
+    fn first_reveal_opens_area() {
+        for seed in 0..20 {
+            let first_coordinate = Coordinate{ x: 4, y: 4 };
+            let board = Board::new_deferred(9, 9, Difficulty::Hard, seed, FirstMove::SafeOpening);
+
+            let updated_board = board.update(&PlayerAction{ player_id: 1, coordinate: first_coordinate, action: Action::Reveal });
+
+            assert_eq!(updated_board.get_tile(&first_coordinate), &TileStatus::Revealed(Tile::Hint(0)));
+            assert_eq!(updated_board.num_mines(), board.num_mines());
+        }
+    }
+
+    #[test]
+    fn flag_does_not_place_mines() {
+        let board = Board::new_deferred(9, 9, Difficulty::Hard, 7, FirstMove::SafeOpening);
+
+        let updated_board = board.update(&PlayerAction{ player_id: 1, coordinate: Coordinate{ x: 4, y: 4 }, action: Action::Flag });
+
+        assert!(!updated_board.mines_placed());
+        assert_eq!(updated_board.get_tile(&Coordinate{ x: 4, y: 4 }), &TileStatus::Hidden);
+    }
+
+    #[test]
+    fn small_board_falls_back_to_safe_tile() {
+        let first_coordinate = Coordinate{ x: 0, y: 0 };
+        let board = Board::new_deferred(2, 2, Difficulty::Easy, 3, FirstMove::SafeOpening);
+
+        let updated_board = board.update(&PlayerAction{ player_id: 1, coordinate: first_coordinate, action: Action::Reveal });
+
+        assert!(!updated_board.is_mine(&first_coordinate));
+        assert_eq!(updated_board.num_mines(), 1);
+    }
+
+    #[test]
+    fn no_guess_board_is_solvable() {
+        let first_coordinate = Coordinate{ x: 0, y: 0 };
+
+        for seed in 0..5 {
+            let board = Board::new_deferred(8, 8, Difficulty::Medium, seed, FirstMove::NoGuess);
+            let updated_board = board.update(&PlayerAction{ player_id: 1, coordinate: first_coordinate, action: Action::Reveal });
+
+            assert!(solver::is_solvable(&updated_board, &first_coordinate));
+            assert!(!updated_board.needs_guess());
+        }
+    }
+
+    #[test]
+    fn no_guess_falls_back_when_every_layout_needs_a_guess() {
+        // 2 mines in the 3 tiles around the first one; nothing tells them apart
+        let first_coordinate = Coordinate{ x: 0, y: 0 };
+        let board = Board::new_deferred(2, 2, Difficulty::Custom { mines: 2 }, 3, FirstMove::NoGuess);
+
+        let updated_board = board.update(&PlayerAction{ player_id: 1, coordinate: first_coordinate, action: Action::Reveal });
+
+        assert!(updated_board.needs_guess());
+        assert!(!updated_board.is_mine(&first_coordinate));
+        assert_eq!(updated_board.num_mines(), 2);
+    }
+
     #[test]
     fn test_update() {
         let test_coordinate: Coordinate = Coordinate{x: 0, y: 0};
@@ -280,6 +653,60 @@ mod tests {
         assert_eq!(*updated_tile_status.unwrap(), TileStatus::Flagged(player.id))
     }
 
+    #[test]
+    fn test_unflag() {
+        let test_coordinate: Coordinate = Coordinate{x: 0, y: 0};
+        let test_board: Board = Board::new_test(2, 2, HashSet::from([test_coordinate]));
+
+        let player = Player::new("hyeyoung".to_string());
+
+        let flagged_board = test_board.update(&PlayerAction{player_id: player.id, coordinate: test_coordinate, action: Action::Flag});
+        let unflagged_board = flagged_board.update(&PlayerAction{player_id: player.id, coordinate: test_coordinate, action: Action::Unflag});
+
+        assert_eq!(unflagged_board.get_tile(&test_coordinate), &TileStatus::Hidden)
+    }
+
+    #[test]
+    fn unflag_hidden_tile_does_nothing() {
+        let test_coordinate: Coordinate = Coordinate{x: 0, y: 0};
+        let test_board: Board = Board::new_test(2, 2, HashSet::from([test_coordinate]));
+
+        let updated_board = test_board.update(&PlayerAction{player_id: 1, coordinate: Coordinate{x: 1, y: 1}, action: Action::Unflag});
+
+        assert_eq!(updated_board.get_tile(&Coordinate{x: 1, y: 1}), &TileStatus::Hidden)
+    }
+
+    #[test]
+    fn test_chord() {
+        let mine_coordinate = Coordinate{ x: 0, y: 0 };
+        let hint_coordinate = Coordinate{ x: 1, y: 0 };
+        let test_board = Board::new_test(3, 3, HashSet::from([mine_coordinate]));
+
+        let board = test_board
+            .update(&PlayerAction{ player_id: 1, coordinate: hint_coordinate, action: Action::Reveal })
+            .update(&PlayerAction{ player_id: 1, coordinate: mine_coordinate, action: Action::Flag });
+        assert!(board.can_chord(&hint_coordinate));
+
+        let chorded_board = board.update(&PlayerAction{ player_id: 1, coordinate: hint_coordinate, action: Action::Chord });
+
+        assert_eq!(chorded_board.get_tile(&Coordinate{ x: 1, y: 1 }), &TileStatus::Revealed(Tile::Hint(1)));
+        assert_eq!(chorded_board.get_tile(&Coordinate{ x: 2, y: 2 }), &TileStatus::Revealed(Tile::Hint(0)));
+        assert_eq!(chorded_board.get_tile(&mine_coordinate), &TileStatus::Flagged(1));
+    }
+
+    #[test]
+    fn chord_without_flags_does_nothing() {
+        let hint_coordinate = Coordinate{ x: 1, y: 0 };
+        let test_board = Board::new_test(3, 3, HashSet::from([Coordinate{ x: 0, y: 0 }]));
+
+        let board = test_board.update(&PlayerAction{ player_id: 1, coordinate: hint_coordinate, action: Action::Reveal });
+        assert!(!board.can_chord(&hint_coordinate));
+
+        let chorded_board = board.update(&PlayerAction{ player_id: 1, coordinate: hint_coordinate, action: Action::Chord });
+
+        assert_eq!(chorded_board.get_tile(&Coordinate{ x: 1, y: 1 }), &TileStatus::Hidden);
+    }
+
     #[test]
     fn test_reveal() {
         let mine_coordinate: HashSet<Coordinate> = HashSet::from([Coordinate{x: 0, y: 0}]);
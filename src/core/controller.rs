@@ -0,0 +1,180 @@
+// Who decides a player's moves: a person at the keyboard or a bot
+//
+// Controllers only get a PlayerView, so a bot plays by the same information as a person
+// and can never ask the board where the mines are.
+
+use std::collections::{HashMap, HashSet};
+use std::io;
+use std::path::PathBuf;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+use crate::core::board::{Coordinate, TileStatus};
+use crate::core::game::Game;
+use crate::core::player::{Action, Player, PlayerAction, PlayerId};
+use crate::core::probability::mine_probabilities;
+use crate::core::solver::deduce;
+use crate::core::view::PlayerView;
+
+// What a controller wants to do on its turn
+#[derive(Debug, Clone, PartialEq)]
+pub enum Choice {
+    Play(PlayerAction),
+    Hint,
+    Undo,
+    Redo,
+    Save(PathBuf),
+}
+
+pub trait PlayerController {
+    // Only a person can fail here, e.g. when the input runs out
+    fn choose(&mut self, view: &PlayerView, player: &Player) -> io::Result<Choice>;
+}
+
+// The players that bots play for, and how; everyone else is a person
+// Kept by whoever runs the game, since the game itself does not care who chooses the moves
+pub type Bots = HashMap<PlayerId, Strategy>;
+
+// How a bot picks its moves
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Strategy {
+    Random,      // reveals any hidden tile
+    Safe,        // plays what the solver can prove, and guesses at random otherwise
+    Probability, // plays what the solver can prove, and otherwise reveals the least likely mine
+}
+
+impl Strategy {
+    pub const ALL: [Strategy; 3] = [Strategy::Random, Strategy::Safe, Strategy::Probability];
+
+    // The same seed makes a bot play the same game the same way
+    pub fn controller(self, seed: u64) -> Box<dyn PlayerController> {
+        Box::new(Bot { strategy: self, rng: StdRng::seed_from_u64(seed) })
+    }
+}
+
+struct Bot {
+    strategy: Strategy,
+    rng: StdRng,
+}
+
+impl PlayerController for Bot {
+    fn choose(&mut self, view: &PlayerView, player: &Player) -> io::Result<Choice> {
+        let (coordinate, action) = match self.strategy {
+            Strategy::Random => (self.random_tile(view), Action::Reveal),
+            Strategy::Safe => proven_move(view).unwrap_or_else(|| (self.random_tile(view), Action::Reveal)),
+            Strategy::Probability => proven_move(view).unwrap_or_else(|| (least_likely_mine(view), Action::Reveal)),
+        };
+
+        Ok(Choice::Play(PlayerAction{ player_id: player.id, coordinate, action }))
+    }
+}
+
+impl Bot {
+    fn random_tile(&mut self, view: &PlayerView) -> Coordinate {
+        let tiles = hidden_tiles(view);
+        tiles[self.rng.gen_range(0..tiles.len())]
+    }
+}
+
+// A tile the solver proves safe to reveal, or else a mine to flag
+fn proven_move(view: &PlayerView) -> Option<(Coordinate, Action)> {
+    let deductions = deduce(view);
+    let first = |tiles: &HashSet<Coordinate>| tiles.iter().copied().min_by_key(|c| (c.y, c.x));
+
+    first(&deductions.safe).map(|coordinate| (coordinate, Action::Reveal))
+        .or_else(|| first(&deductions.mines).map(|coordinate| (coordinate, Action::Flag)))
+}
+
+fn least_likely_mine(view: &PlayerView) -> Coordinate {
+    mine_probabilities(view).into_iter()
+        .min_by(|(a, p), (b, q)| p.total_cmp(q).then((a.y, a.x).cmp(&(b.y, b.x))))
+        .map(|(coordinate, _)| coordinate)
+        .expect("a bot only plays while tiles are hidden")
+}
+
+// Row by row, so that a seeded bot does not depend on the order of the board's map
+fn hidden_tiles(view: &PlayerView) -> Vec<Coordinate> {
+    let mut tiles: Vec<Coordinate> = view.iter()
+        .filter(|(_, tile_status)| matches!(tile_status, TileStatus::Hidden))
+        .map(|(coordinate, _)| *coordinate)
+        .collect();
+    tiles.sort_by_key(|c| (c.y, c.x));
+
+    assert!(!tiles.is_empty(), "a bot only plays while tiles are hidden");
+    tiles
+}
+
+// A controller for every player: `bots` play by their strategy, and `human` makes one for everyone else
+// Each bot's random numbers come from the game's seed and its seat, not its id, so a seeded game replays the same way
+pub fn controllers(game: &Game, bots: &Bots, human: impl Fn() -> Box<dyn PlayerController>) -> HashMap<PlayerId, Box<dyn PlayerController>> {
+    let mut players: Vec<&Player> = game.players.values().collect();
+    players.sort_by_key(|player| player.id);
+
+    players.into_iter().enumerate()
+        .map(|(seat, player)| {
+            let controller = match bots.get(&player.id).copied() {
+                Some(strategy) => strategy.controller(game.seed() ^ (seat as u64 + 1).wrapping_mul(0x9e37_79b9_7f4a_7c15)),
+                None => human(),
+            };
+            (player.id, controller)
+        })
+        .collect()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::board::Board;
+
+    fn play(strategy: Strategy, view: &PlayerView) -> PlayerAction {
+        let player = Player::new_with_id(1, "bot");
+
+        match strategy.controller(0).choose(view, &player).unwrap() {
+            Choice::Play(player_action) => player_action,
+            choice => panic!("bots only play moves, not {:?}", choice),
+        }
+    }
+
+    #[test]
+    fn bots_play_what_is_proven() {
+        // ? 1 .    one mine in (0,0) or (0,1), and nothing tells them apart
+        // ? 1 .
+        let board = Board::new_test(3, 2, HashSet::from([Coordinate{ x: 0, y: 0 }]))
+            .update(&PlayerAction{ player_id: 1, coordinate: Coordinate{ x: 2, y: 0 }, action: Action::Reveal });
+        let view = board.view();
+
+        assert_eq!(hidden_tiles(&view).len(), 2);
+        assert_eq!(play(Strategy::Probability, &view).coordinate, Coordinate{ x: 0, y: 0 });
+
+        // with one flagged, the other is proven safe
+        let flagged = board.update(&PlayerAction{ player_id: 1, coordinate: Coordinate{ x: 0, y: 0 }, action: Action::Flag });
+        assert_eq!(play(Strategy::Safe, &flagged.view()), PlayerAction{ player_id: 1, coordinate: Coordinate{ x: 0, y: 1 }, action: Action::Reveal });
+    }
+
+    #[test]
+    fn safe_bot_flags_proven_mines() {
+        // ? 1 .
+        // 1 1 .
+        // . . .
+        let board = Board::new_test(3, 3, HashSet::from([Coordinate{ x: 0, y: 0 }]))
+            .update(&PlayerAction{ player_id: 1, coordinate: Coordinate{ x: 2, y: 2 }, action: Action::Reveal });
+
+        assert_eq!(play(Strategy::Safe, &board.view()), PlayerAction{ player_id: 1, coordinate: Coordinate{ x: 0, y: 0 }, action: Action::Flag });
+    }
+
+    #[test]
+    fn seeded_bots_repeat_themselves() {
+        let view = Board::new(9, 9, crate::core::game::Difficulty::Easy, 3).view();
+        let player = Player::new_with_id(1, "bot");
+        let moves = |seed| {
+            let mut bot = Strategy::Random.controller(seed);
+            (0..5).map(|_| bot.choose(&view, &player).unwrap()).collect::<Vec<Choice>>()
+        };
+
+        assert_eq!(moves(7), moves(7));
+        assert_ne!(moves(7), moves(8));
+    }
+}
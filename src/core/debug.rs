@@ -0,0 +1,110 @@
+// Debug log: mine positions, solver reasoning and state transitions
+//
+// Off unless a log file is opened with `enable` (the `--debug <file>` flag).
+// It only ever writes to that file, so the console never shows where the mines are.
+
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::Path;
+use std::sync::{Mutex, OnceLock, PoisonError};
+
+use crate::core::board::{Board, Coordinate, Tile, TileStatus};
+
+static LOG: OnceLock<Mutex<File>> = OnceLock::new();
+
+// Opens the log file; a program can only log to one file
+pub fn enable(path: &Path) -> io::Result<()> {
+    let file = File::create(path)?;
+
+    LOG.set(Mutex::new(file))
+        .map_err(|_| io::Error::other("the debug log is already open"))
+}
+
+pub fn is_enabled() -> bool {
+    LOG.get().is_some()
+}
+
+// Takes a closure so that a normal game does not even build the message
+pub fn log(message: impl FnOnce() -> String) {
+    if let Some(file) = LOG.get() {
+        let mut file = file.lock().unwrap_or_else(PoisonError::into_inner);
+        let _ = writeln!(file, "{}", message());
+    }
+}
+
+// Sorted row by row, e.g. (2,0) (0,1)
+pub fn coordinates<'a>(coordinates: impl IntoIterator<Item = &'a Coordinate>) -> String {
+    let mut coordinates: Vec<&Coordinate> = coordinates.into_iter().collect();
+    coordinates.sort_by_key(|coordinate| (coordinate.y, coordinate.x));
+
+    coordinates.iter()
+        .map(|coordinate| format!("({},{})", coordinate.x, coordinate.y))
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+// The board with every mine showing: * is a mine, a number a hint, then what the players see
+// For example,
+//   * 1 .    ? ? .
+//   1 1 .    ? 1 .
+pub fn mine_map(board: &Board) -> String {
+    let mut lines = Vec::new();
+
+    for y in 0..board.v_size {
+        let mines: Vec<String> = (0..board.h_size)
+            .map(|x| Coordinate{ x, y })
+            .map(|coordinate| match board.get_hint(&coordinate) {
+                _ if board.is_mine(&coordinate) => "*".to_string(),
+                0 => ".".to_string(),
+                hint => hint.to_string(),
+            })
+            .collect();
+        let seen: Vec<String> = (0..board.h_size)
+            .map(|x| match board.get_tile(&Coordinate{ x, y }) {
+                TileStatus::Hidden => "?".to_string(),
+                TileStatus::Flagged(_) => "!".to_string(),
+                TileStatus::Revealed(Tile::Mine) => "*".to_string(),
+                TileStatus::Revealed(Tile::Hint(0)) => ".".to_string(),
+                TileStatus::Revealed(Tile::Hint(hint)) => hint.to_string(),
+            })
+            .collect();
+
+        lines.push(format!("{}    {}", mines.join(" "), seen.join(" ")));
+    }
+
+    lines.join("\n")
+}
+
+// What a solver rule found, if anything
+pub fn deductions(rule: &str, safe: &HashSet<Coordinate>, mines: &HashSet<Coordinate>) -> Option<String> {
+    if safe.is_empty() && mines.is_empty() {
+        return None;
+    }
+
+    Some(format!("solver, {}: safe [{}] mines [{}]", rule, coordinates(safe), coordinates(mines)))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::player::{Action, PlayerAction};
+
+    #[test]
+    fn shows_mines_and_what_players_see() {
+        let board = Board::new_test(3, 2, HashSet::from([Coordinate{ x: 0, y: 0 }]))
+            .update(&PlayerAction{ player_id: 1, coordinate: Coordinate{ x: 1, y: 1 }, action: Action::Reveal });
+
+        assert_eq!(mine_map(&board), "* 1 .    ? ? ?\n1 1 .    ? 1 ?");
+    }
+
+    #[test]
+    fn lists_coordinates_row_by_row() {
+        let mines = HashSet::from([Coordinate{ x: 0, y: 1 }, Coordinate{ x: 2, y: 0 }]);
+
+        assert_eq!(coordinates(&mines), "(2,0) (0,1)");
+        assert_eq!(deductions("subset rule", &HashSet::new(), &mines).unwrap(), "solver, subset rule: safe [] mines [(2,0) (0,1)]");
+        assert!(deductions("global rule", &HashSet::new(), &HashSet::new()).is_none());
+    }
+}
@@ -1,39 +1,148 @@
 use crate::core::player::{Player, PlayerId, PlayerAction, Action};
-use crate::core::board::{Board, Tile, TileStatus};
+use crate::core::board::{Board, Coordinate, Tile, TileStatus};
+use crate::core::view::PlayerView;
+use crate::core::turn::{TurnOrder, Turns};
+use crate::core::debug;
+use crate::core::validation::{validate_action, validate_coordinate, CoordinateErr, InvalidErr};
 
-use im::HashMap;
+use im::{HashMap, HashSet};
+use serde::{Deserialize, Serialize};
 
 pub const EASY: f32 = 0.12;
 pub const MEDIUM: f32 = 0.15;
 pub const HARD: f32 = 0.2;
 
+pub const BEGINNER_MINES: u32 = 10;
+pub const INTERMEDIATE_MINES: u32 = 40;
+pub const EXPERT_MINES: u32 = 99;
+
+// What a hint costs when points decide the game
+pub const HINT_COST: i32 = 3;
+
+#[derive(Clone)]
 pub struct Game {
     pub board: Board,
     pub players: HashMap<PlayerId, Player>,
-    pub status: GameStatus
+    pub status: GameStatus,
+    pub rules: Rules,
+    pub(crate) eliminated: HashSet<PlayerId>, // players knocked out under Rules::Elimination
+    pub turns: Turns,
+    pub mode: Mode,
+    pub(crate) boards: HashMap<PlayerId, Board>, // each player's own board under Mode::ParallelBoards
+    pub(crate) progress: HashMap<PlayerId, Progress>,
+}
+
+// Whether the players share one board
+#[derive(PartialEq, Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub enum Mode {
+    #[default]
+    SharedBoard,
+    ParallelBoards, // every player clears their own copy of the board; `board` is the copy they start from
+}
+
+// How a player is doing, for the final ranking
+#[derive(PartialEq, Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct Progress {
+    pub moves: u32,
+    pub mistakes: u32,              // mines revealed and flags on safe tiles
+    pub completed_at: Option<u32>,  // the game's move number when the player cleared their own board
+    #[serde(default)]
+    pub last_mine: Option<(u32, Coordinate)>, // the game's move number and tile of the last mine the player revealed
 }
 
-#[derive(PartialEq, Debug)]
+#[derive(PartialEq, Debug, Clone, Copy, Serialize, Deserialize)]
 pub enum GameStatus {
     Continue,
-    Over,
+    Won,
+    Lost(PlayerId, Coordinate), // who revealed which mine
 }
 
-#[derive(Clone)]
+// What revealing a mine means
+#[derive(PartialEq, Debug, Clone, Copy, Serialize, Deserialize)]
+pub enum Rules {
+    Classic,     // the game is lost; it is won once every safe tile is revealed
+    Elimination, // the player is knocked out; the game is lost once every player is out
+    PointsOnly,  // the player loses 10 points; the game ends once every tile is revealed or flagged
+}
+
+// How many mines a board gets
+// Easy, Medium and Hard are a share of the tiles; the classic presets also fix the board size
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Difficulty {
     Easy,
     Medium,
-    Hard
+    Hard,
+    Beginner,              // 9x9 with 10 mines
+    Intermediate,          // 16x16 with 40 mines
+    Expert,                // 30x16 with 99 mines
+    Custom { mines: u32 }, // exactly this many mines, e.g. from `--mines`
+    Density(f32),          // this share of the tiles, from 0 up to but not including 1
+}
+
+impl Difficulty {
+    // The board size a classic preset is played on
+    pub fn board_size(&self) -> Option<(u32, u32)> {
+        match self {
+            Difficulty::Beginner => Some((9, 9)),
+            Difficulty::Intermediate => Some((16, 16)),
+            Difficulty::Expert => Some((30, 16)),
+            _ => None,
+        }
+    }
 }
 
 impl Game {
-    pub fn new(h_size: u32, v_size: u32, difficulty: Difficulty) -> Game {
+    pub fn new(h_size: u32, v_size: u32, difficulty: Difficulty, seed: u64) -> Game {
+        Game::from_board(Board::new(h_size, v_size, difficulty, seed))
+    }
+
+    // Starts a game on an already built board, e.g. `Board::new_deferred`
+    pub fn from_board(board: Board) -> Game {
         Game {
-            board: Board::new(h_size, v_size, difficulty),
+            board,
             players: HashMap::new(),
             status: GameStatus::Continue,
+            rules: Rules::PointsOnly,
+            eliminated: HashSet::new(),
+            turns: Turns::new(TurnOrder::RoundRobin, false),
+            mode: Mode::SharedBoard,
+            boards: HashMap::new(),
+            progress: HashMap::new(),
         }
     }
+
+    // Rules are chosen while setting up the game, like players
+    //   let game = Game::new(10, 10, Difficulty::Medium, seed)
+    //     .with_rules(Rules::Classic)
+    //     .add_player(Player::new("hyeyoung"));
+    pub fn with_rules(self, rules: Rules) -> Game {
+        Game { rules, ..self }
+    }
+
+    // With parallel boards, every player gets a copy of `board`
+    // Copies of a deferred board place their mines on the player's own first move,
+    // so every player gets the same seed and a safe start
+    pub fn with_mode(self, mode: Mode) -> Game {
+        let boards = match mode {
+            Mode::SharedBoard => HashMap::new(),
+            Mode::ParallelBoards => self.players.keys().map(|id| (*id, self.board.clone())).collect(),
+        };
+
+        Game { mode, boards, ..self }
+    }
+
+    pub fn with_turn_order(self, order: TurnOrder, extra_turn_on_flag: bool) -> Game {
+        let turns = Turns::new(order, extra_turn_on_flag);
+
+        Game { turns, ..self }.restart_turns()
+    }
+
+    // Starts the first round over, e.g. once a player joins
+    fn restart_turns(self) -> Game {
+        let turns = self.turns.start_round(0, &self.active_players(), self.seed());
+
+        Game { turns, ..self }
+    }
     
     // once a player is added to a game, the game owns that player
     // The Rust Guideline:  
@@ -41,84 +150,294 @@ impl Game {
     // When You WOULD Borrow:
     //   Only if the caller legitimately needs the player afterwards:
     pub fn add_player(self, player: Player) -> Game {
+        let boards = match self.mode {
+            Mode::SharedBoard => self.boards.clone(),
+            Mode::ParallelBoards => self.boards.update(player.id, self.board.clone()),
+        };
+
         Game {
-            board: self.board,
             players: self.players.update(player.id, player),
-            status: self.status, 
-        }
+            boards,
+            ..self
+        }.restart_turns()
+    }
+
+    // Only before play starts, e.g. when someone leaves a lobby
+    pub fn remove_player(self, player_id: &PlayerId) -> Game {
+        Game {
+            players: self.players.without(player_id),
+            boards: self.boards.without(player_id),
+            ..self
+        }.restart_turns()
     }
 
     // this way I can chain add_player to game
     // for example,
-    //   let mut game = Game::new(10, 10, Difficulty::Medium)
+    //   let mut game = Game::new(10, 10, Difficulty::Medium, seed)
     //     .add_player(Player::new("charlie")
     //     .add_player(Player::new("hyeyoung")
     //     .add_player(Player::new("william");
     pub fn add_player_by_name(self, player_name: &str) -> Game{
         let player = Player::new(player_name.to_string());
 
-        Game {
-            board: self.board,
-            players: self.players.update(player.id, player),
-            status: self.status,
-        }
+        self.add_player(player)
+    }
+
+    // The board as the players see it
+    pub fn view(&self) -> PlayerView {
+        self.board.view()
+    }
+
+    // The board a player plays on: their own with parallel boards, the shared one otherwise
+    pub fn player_board(&self, player_id: &PlayerId) -> &Board {
+        self.boards.get(player_id).unwrap_or(&self.board)
+    }
+
+    pub fn player_view(&self, player_id: &PlayerId) -> PlayerView {
+        self.player_board(player_id).view()
+    }
+
+    pub fn progress(&self, player_id: &PlayerId) -> Progress {
+        self.progress.get(player_id).copied().unwrap_or_default()
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.board.seed
     }
 
     pub fn get_player(&self, player_id: &PlayerId) -> &Player {
         self.players.get(player_id).unwrap_or_else(|| panic!("no player with id: {player_id} found"))
     }
 
-    // pub fn current_player(&self) -> &Player {
-    //     let id = self.turn_order[self.current_turn];
-    //     self.get_player(&id)
-    // }
+    // None in a race, where everyone can move at any time
+    pub fn current_player(&self) -> Option<&Player> {
+        self.turns.current_player().map(|id| self.get_player(&id))
+    }
+
+    pub fn is_eliminated(&self, player_id: &PlayerId) -> bool {
+        self.eliminated.contains(player_id)
+    }
+
+    // A player who leaves is out of the game, and their turn passes on
+    // Once nobody is left to play, the game ends as it would have on their last move
+    pub fn forfeit(&self, player_id: &PlayerId) -> Game {
+        let game = Game { eliminated: self.eliminated.update(*player_id), ..self.clone() };
+        let turns = if self.turns.current_player() == Some(*player_id) {
+            game.turns.next(false, &game.active_players(), self.seed())
+        } else {
+            game.turns.clone()
+        };
+        let status = match self.status {
+            GameStatus::Continue => game.progress_status(),
+            status => status,
+        };
+
+        Game { turns, status, ..game }
+    }
+
+    // Players who can still take turns
+    // With parallel boards, a player who cleared their board is done
+    pub fn active_players(&self) -> Vec<PlayerId> {
+        self.players.keys()
+            .filter(|id| !self.is_eliminated(id) && self.progress(id).completed_at.is_none())
+            .copied()
+            .collect()
+    }
+
+    // This function validates that the player may move now, and that the move fits the tile
+    fn validate_turn(&self, player_action: &PlayerAction) -> Result<(), InvalidErr> {
+        if !self.active_players().contains(&player_action.player_id) {
+            Err(InvalidErr::InvalidPlayer)
+        } else if self.turns.order == TurnOrder::Simultaneous {
+            self.validate_race(player_action)
+        } else if self.turns.current_player() != Some(player_action.player_id) {
+            Err(InvalidErr::NotYourTurn)
+        } else {
+            self.validate_move(player_action)
+        }
+    }
+
+    // The same checks as the prompts, for callers that skip them, e.g. bots and replays
+    fn validate_move(&self, player_action: &PlayerAction) -> Result<(), InvalidErr> {
+        let view = self.player_view(&player_action.player_id);
+        let player = self.get_player(&player_action.player_id);
+
+        validate_coordinate(&view, &player_action.coordinate, player)
+            .and_then(|coordinate| validate_action(&view, *player_action, &coordinate))
+            .map(|_| ())
+    }
+
+    // In a race, players choose moves on boards that other players' moves may already have changed
+    // The first move to reach a tile wins it; a later move on a tile that is no longer hidden is rejected as too late
+    // A move that would not have fit the hidden tile either, e.g. unflagging it, is simply invalid
+    fn validate_race(&self, player_action: &PlayerAction) -> Result<(), InvalidErr> {
+        let takes_hidden_tile = matches!(player_action.action, Action::Reveal | Action::Flag | Action::Hint);
 
-    // TODO: reimplement game status update logic
-    pub fn update_status(board: &Board) -> GameStatus {
-        let game_over = board.iter().all(|(_, tile_status)| {
-            matches!(tile_status, TileStatus::Revealed(_)) || matches!(tile_status, TileStatus::Flagged(_))
-        });
+        match self.validate_move(player_action) {
+            result @ Err(InvalidErr::InvalidCoordinate(CoordinateErr::OutOfBounds)) => result,
+            Err(_) if takes_hidden_tile && self.player_board(&player_action.player_id).get_tile(&player_action.coordinate) != &TileStatus::Hidden => {
+                Err(InvalidErr::TileTaken)
+            },
+            result => result,
+        }
+    }
 
-        match game_over {
-            true => GameStatus::Over,
-            false => GameStatus::Continue,
+    fn update_status(&self, player_action: &PlayerAction, mine_hit: Option<Coordinate>, board: &Board, eliminated: &HashSet<PlayerId>) -> GameStatus {
+        match (self.rules, mine_hit) {
+            (Rules::Classic, Some(coordinate)) => GameStatus::Lost(player_action.player_id, coordinate),
+            (Rules::Elimination, Some(coordinate)) if eliminated.len() == self.players.len() => {
+                GameStatus::Lost(player_action.player_id, coordinate)
+            },
+            _ if self.is_cleared(board) => GameStatus::Won,
+            _ => GameStatus::Continue,
         }
     }
 
+    fn is_cleared(&self, board: &Board) -> bool {
+        match self.rules {
+            Rules::Classic | Rules::Elimination => board.iter().all(|(coordinate, tile_status)| {
+                board.is_mine(coordinate) || matches!(tile_status, TileStatus::Revealed(_))
+            }),
+            Rules::PointsOnly => board.iter().all(|(_, tile_status)| {
+                matches!(tile_status, TileStatus::Revealed(_)) || matches!(tile_status, TileStatus::Flagged(_))
+            }),
+        }
+    }
+
+    // The mine revealed by this action, if any
+    // Only the chosen tile and, for a chord, its neighbors can be revealed as a mine
+    fn mine_hit(player_action: &PlayerAction, board: &Board, updated_board: &Board) -> Option<Coordinate> {
+        let mut candidates = updated_board.neighboring_coordinates(&player_action.coordinate);
+        candidates.insert(0, player_action.coordinate);
+
+        candidates.into_iter().find(|c| {
+            !matches!(board.get_tile(c), TileStatus::Revealed(Tile::Mine))
+                && matches!(updated_board.get_tile(c), TileStatus::Revealed(Tile::Mine))
+        })
+    }
+
+    // The status once nobody is left to play: won if anyone cleared their board, lost on the last mine revealed otherwise
+    // This decides every game with parallel boards, where whoever cleared their board first wins,
+    // and a shared board game that everyone left
+    fn progress_status(&self) -> GameStatus {
+        let anyone_completed = self.progress.values().any(|progress| progress.completed_at.is_some());
+        let last_mine = self.progress.iter()
+            .filter_map(|(player_id, progress)| progress.last_mine.map(|(move_number, coordinate)| (move_number, *player_id, coordinate)))
+            .max_by_key(|(move_number, _, _)| *move_number);
+
+        match (self.active_players().is_empty(), anyone_completed, last_mine) {
+            (false, _, _) => GameStatus::Continue,
+            (true, true, _) => GameStatus::Won,
+            (true, false, Some((_, player_id, coordinate))) => GameStatus::Lost(player_id, coordinate),
+            (true, false, None) => GameStatus::Continue, // everyone left before anything happened
+        }
+    }
+
+    // Best first; players who cleared their board rank by when they did,
+    // everyone else by points. Fewer mistakes breaks ties.
+    fn rank_key(&self, player: &Player) -> (bool, u32, i32, u32) {
+        let progress = self.progress(&player.id);
+
+        match progress.completed_at {
+            Some(completed_at) => (false, completed_at, 0, progress.mistakes),
+            None => (true, 0, -player.points, progress.mistakes),
+        }
+    }
+
+    // Every player, best first
+    // With a shared board, points decide; with parallel boards, see rank_key
+    pub fn ranking(&self) -> Vec<&Player> {
+        let mut players: Vec<&Player> = self.players.values().collect();
+
+        match self.mode {
+            Mode::SharedBoard => players.sort_by_key(|player| (self.is_eliminated(&player.id), -player.points, player.id)),
+            Mode::ParallelBoards => players.sort_by_key(|player| (self.is_eliminated(&player.id), self.rank_key(player), player.id)),
+        }
+
+        players
+    }
+
+    // Eliminated players cannot win
     pub fn get_winners(&self) -> Vec<&Player> {
-        if self.players.is_empty() {
-          return Vec::new();
+        if self.mode == Mode::ParallelBoards {
+            let ranking: Vec<&Player> = self.ranking().into_iter()
+                .filter(|player| !self.is_eliminated(&player.id))
+                .collect();
+
+            return match ranking.first() {
+                Some(best) => ranking.iter().copied().filter(|player| self.rank_key(player) == self.rank_key(best)).collect(),
+                None => Vec::new(),
+            };
         }
+
+        let remaining_players: Vec<&Player> = self.players.values()
+            .filter(|p| !self.is_eliminated(&p.id))
+            .collect();
+
+        let max_score = match remaining_players.iter().map(|p| p.points).max() {
+            Some(max_score) => max_score,
+            None => return Vec::new(),
+        };
         
-        let max_score = self.players.values()
-            .map(|p| p.points)
-            .max()
-            .unwrap();
-        
-        self.players.values()
+        remaining_players.into_iter()
           .filter(|p| p.points == max_score)
           .collect()
     }
 
-    fn calculate_points(player_action: &PlayerAction, board: &Board) -> i32 {
-        match player_action.action {
-            Action::Reveal => {
-                match board.get_tile(&player_action.coordinate) {
-                    TileStatus::Revealed(Tile::Hint(n)) => match n {
-                        0 => 3,
-                        _ => 1
-                    },
-                    TileStatus::Revealed(Tile::Mine) => -10,
-                    _ => 0 // panic!("tile should have been revealed!")
-                }
+    fn reveal_points(tile_status: &TileStatus) -> i32 {
+        match tile_status {
+            TileStatus::Revealed(Tile::Hint(n)) => match n {
+                0 => 3,
+                _ => 1
             },
+            TileStatus::Revealed(Tile::Mine) => -10,
+            _ => 0 // panic!("tile should have been revealed!")
+        }
+    }
+
+    // Points decide the winner between several players, and a points-only game on its own
+    // A single player in a classic game plays to clear the board, so hints are free there
+    pub fn is_scored(&self) -> bool {
+        self.players.len() > 1 || self.rules == Rules::PointsOnly
+    }
+
+    // `board` is the board before the action, `updated_board` the board after it
+    fn calculate_points(&self, player_action: &PlayerAction, board: &Board, updated_board: &Board) -> i32 {
+        match player_action.action {
+            Action::Reveal => Self::reveal_points(updated_board.get_tile(&player_action.coordinate)),
             Action::Flag => {
-                if board.is_mine(&player_action.coordinate) {
+                if updated_board.is_mine(&player_action.coordinate) {
                     2
                 } else {
                     -1
                 }
-            }
+            },
+            // only mines stay flagged, so retracting a flag takes back what flagging earned
+            Action::Unflag => {
+                if updated_board.is_mine(&player_action.coordinate) {
+                    -2
+                } else {
+                    0
+                }
+            },
+            // each neighbor the chord opened scores as if it had been revealed on its own
+            Action::Chord => {
+                board.neighboring_coordinates(&player_action.coordinate).iter()
+                    .filter(|c| matches!(board.get_tile(c), TileStatus::Hidden))
+                    .map(|c| Self::reveal_points(updated_board.get_tile(c)))
+                    .sum()
+            },
+            Action::Hint if self.is_scored() => -HINT_COST,
+            Action::Hint => 0,
+        }
+    }
+
+    pub fn subtract_points(&self, player_id: &PlayerId, points: i32) -> Game {
+        let updated_player = self.get_player(player_id).subtract_points(points);
+
+        Game {
+            players: self.players.update(*player_id, updated_player),
+            ..self.clone()
         }
     }
 
@@ -129,24 +448,96 @@ impl Game {
     }
 
     // Updates board_map and GameStatus
-    pub fn update(&self, player_action: &PlayerAction) -> Game {
-        // 1. update board
-        let updated_board = self.board.update(player_action);
+    // Actions from players who are not in turn, or after the game is over, are rejected
+    pub fn update(&self, player_action: &PlayerAction) -> Result<Game, InvalidErr> {
+        // 0. check that the game is on, whose turn it is, and that there is something to flag
+        if self.status != GameStatus::Continue {
+            return Err(InvalidErr::GameOver);
+        }
+        self.validate_turn(player_action)?;
+        if matches!(player_action.action, Action::Flag | Action::Unflag) && !self.player_board(&player_action.player_id).mines_placed() {
+            return Err(InvalidErr::NoMinesYet);
+        }
+
+        // 1. update the player's board
+        let board = self.player_board(&player_action.player_id);
+        let updated_board = board.update(player_action);
 
         // 2. calculate points based on updated_board
-        let points = Self::calculate_points(player_action, &updated_board);
+        let points = self.calculate_points(player_action, board, &updated_board);
 
         // 3. award points
         let updated_players = self.award_points(player_action, points);
-        
-        // 4. update game status
-        let updated_status = Game::update_status(&updated_board);
-        
-        Game {
-            board: updated_board,
+
+        // 4. knock out a player who revealed a mine
+        // with parallel boards, a mine ends the player's own game unless only points are at stake
+        let mine_hit = Self::mine_hit(player_action, board, &updated_board);
+        let updated_eliminated = match (self.mode, self.rules, mine_hit) {
+            (_, Rules::Elimination, Some(_)) | (Mode::ParallelBoards, Rules::Classic, Some(_)) => {
+                self.eliminated.update(player_action.player_id)
+            },
+            _ => self.eliminated.clone(),
+        };
+
+        // 5. keep track of moves and mistakes
+        let updated_progress = self.update_progress(player_action, mine_hit, &updated_board);
+
+        // 6. put the board back
+        let flagged_mine = player_action.action == Action::Flag && updated_board.is_mine(&player_action.coordinate);
+        let updated_game = match self.mode {
+            Mode::SharedBoard => Game { board: updated_board, ..self.clone() },
+            Mode::ParallelBoards => Game { boards: self.boards.update(player_action.player_id, updated_board), ..self.clone() },
+        };
+        let updated_game = Game {
             players: updated_players,
-            status: updated_status,
+            eliminated: updated_eliminated,
+            progress: updated_progress,
+            ..updated_game
+        };
+
+        // 7. update game status
+        let updated_status = match self.mode {
+            Mode::SharedBoard => self.update_status(player_action, mine_hit, &updated_game.board, &updated_game.eliminated),
+            Mode::ParallelBoards => updated_game.progress_status(),
+        };
+
+        // 8. pass the turn on, unless the player only asked for a hint
+        let updated_turns = match player_action.action {
+            Action::Hint => self.turns.clone(),
+            _ => self.turns.next(flagged_mine, &updated_game.active_players(), self.seed()),
+        };
+
+        let knocked_out = !self.is_eliminated(&player_action.player_id) && updated_game.is_eliminated(&player_action.player_id);
+        debug::log(|| format!("player {} {:?} at ({},{}): {:+} points{}, status {:?} -> {:?}, next turn {:?}",
+            player_action.player_id, player_action.action, player_action.coordinate.x, player_action.coordinate.y, points,
+            if knocked_out { ", knocked out" } else { "" }, self.status, updated_status, updated_turns.current_player()));
+
+        Ok(Game { turns: updated_turns, status: updated_status, ..updated_game })
+    }
+
+    fn update_progress(&self, player_action: &PlayerAction, mine_hit: Option<Coordinate>, updated_board: &Board) -> HashMap<PlayerId, Progress> {
+        // a hint is not a move
+        if player_action.action == Action::Hint {
+            return self.progress.clone();
         }
+
+        let progress = self.progress(&player_action.player_id);
+        let flagged_safe_tile = player_action.action == Action::Flag && !updated_board.is_mine(&player_action.coordinate);
+        let move_number = self.progress.values().map(|progress| progress.moves).sum::<u32>() + 1;
+
+        let completed_at = match self.mode {
+            Mode::ParallelBoards if progress.completed_at.is_none() && self.is_cleared(updated_board) => Some(move_number),
+            _ => progress.completed_at,
+        };
+
+        let updated_progress = Progress {
+            moves: progress.moves + 1,
+            mistakes: progress.mistakes + (mine_hit.is_some() || flagged_safe_tile) as u32,
+            completed_at,
+            last_mine: mine_hit.map(|coordinate| (move_number, coordinate)).or(progress.last_mine),
+        };
+
+        self.progress.update(player_action.player_id, updated_progress)
     }
 }
 
@@ -154,10 +545,11 @@ impl Game {
 #[cfg(test)]
 mod tests {
     use super::*; // bring all of the items belonging to the tests module’s parent into scope
+    use std::collections::HashSet;
 
     #[test]
     fn add_player() {
-        let mut game = Game::new(3, 3, Difficulty::Easy);
+        let mut game = Game::new(3, 3, Difficulty::Easy, 0);
         let player_1 = Player::new_with_id(1, "hyeyoung");
         // let updated_game = game.add_player(player_1);
         game = game.add_player(player_1);
@@ -167,7 +559,7 @@ mod tests {
     
     #[test]
     fn add_players() {
-        let mut game = Game::new(3, 3, Difficulty::Easy);
+        let mut game = Game::new(3, 3, Difficulty::Easy, 0);
         let player_1 = Player::new_with_id(1, "hyeyoung");
         let player_2= Player::new_with_id(2,"charlie");
         game = game.add_player(player_1);
@@ -176,9 +568,338 @@ mod tests {
         assert_eq!(game.get_player(&2).id, 2);
     }
 
+    #[test]
+    fn unflag_takes_back_flag_points() {
+        let mine_coordinate = Coordinate{ x: 0, y: 0 };
+        let game = Game::from_board(Board::new_test(3, 3, HashSet::from([mine_coordinate])))
+            .add_player(Player::new_with_id(1, "hyeyoung"));
+
+        let flagged_game = game.update(&PlayerAction{ player_id: 1, coordinate: mine_coordinate, action: Action::Flag }).unwrap();
+        assert_eq!(flagged_game.get_player(&1).points, 2);
+
+        let unflagged_game = flagged_game.update(&PlayerAction{ player_id: 1, coordinate: mine_coordinate, action: Action::Unflag }).unwrap();
+        assert_eq!(unflagged_game.get_player(&1).points, 0);
+        assert_eq!(unflagged_game.board.get_tile(&mine_coordinate), &TileStatus::Hidden);
+    }
+
+    #[test]
+    fn chord_scores_every_opened_neighbor() {
+        // ! ? ?
+        // ? 1 ?  <- only (1,1) is revealed and the mine at (0,0) is flagged
+        // ? ? ?
+        let mine_coordinate = Coordinate{ x: 0, y: 0 };
+        let hint_coordinate = Coordinate{ x: 1, y: 1 };
+        let game = Game::from_board(Board::new_test(3, 3, HashSet::from([mine_coordinate])))
+            .add_player(Player::new_with_id(1, "hyeyoung"))
+            .update(&PlayerAction{ player_id: 1, coordinate: hint_coordinate, action: Action::Reveal }).unwrap()
+            .update(&PlayerAction{ player_id: 1, coordinate: mine_coordinate, action: Action::Flag }).unwrap();
+        assert_eq!(game.get_player(&1).points, 3);
+
+        let chorded_game = game.update(&PlayerAction{ player_id: 1, coordinate: hint_coordinate, action: Action::Chord }).unwrap();
+
+        // (1,0) and (0,1) are hints worth 1, the other 5 neighbors are zeros worth 3
+        assert_eq!(chorded_game.get_player(&1).points, 3 + 1 + 1 + 5 * 3);
+    }
+
+    fn reveal(game: &Game, player_id: PlayerId, x: u32, y: u32) -> Game {
+        game.update(&PlayerAction{ player_id, coordinate: Coordinate{ x, y }, action: Action::Reveal }).unwrap()
+    }
+
+    fn two_player_game(rules: Rules) -> Game {
+        Game::from_board(Board::new_test(3, 3, HashSet::from([Coordinate{ x: 0, y: 0 }])))
+            .with_rules(rules)
+            .add_player(Player::new_with_id(1, "hyeyoung"))
+            .add_player(Player::new_with_id(2, "charlie"))
+    }
+
+    #[test]
+    fn classic_lost_on_mine() {
+        let game = reveal(&two_player_game(Rules::Classic), 1, 0, 0);
+
+        assert_eq!(game.status, GameStatus::Lost(1, Coordinate{ x: 0, y: 0 }));
+    }
+
+    #[test]
+    fn no_moves_after_the_game_is_over() {
+        let game = Game::from_board(Board::new_test(3, 3, HashSet::from([Coordinate{ x: 0, y: 0 }])))
+            .with_rules(Rules::Classic)
+            .add_player(Player::new_with_id(1, "hyeyoung"));
+        let game = reveal(&game, 1, 0, 0);
+        let player_action = PlayerAction{ player_id: 1, coordinate: Coordinate{ x: 2, y: 2 }, action: Action::Reveal };
+
+        assert_eq!(game.status, GameStatus::Lost(1, Coordinate{ x: 0, y: 0 }));
+        assert!(matches!(game.update(&player_action), Err(InvalidErr::GameOver)));
+    }
+
+    #[test]
+    fn moves_that_do_not_fit_the_tile_are_rejected() {
+        let game = Game::from_board(Board::new_test(3, 3, HashSet::from([Coordinate{ x: 0, y: 0 }])))
+            .add_player(Player::new_with_id(1, "hyeyoung"));
+        let play = |game: &Game, x, y, action| game.update(&PlayerAction{ player_id: 1, coordinate: Coordinate{ x, y }, action });
+
+        assert!(matches!(play(&game, 5, 5, Action::Reveal), Err(InvalidErr::InvalidCoordinate(CoordinateErr::OutOfBounds))));
+        assert!(matches!(play(&game, 0, 0, Action::Unflag), Err(InvalidErr::InvalidAction)));
+
+        let flagged = play(&game, 0, 0, Action::Flag).unwrap();
+        assert!(matches!(play(&flagged, 0, 0, Action::Flag), Err(InvalidErr::InvalidAction)));
+
+        let revealed = reveal(&game, 1, 1, 1);
+        assert!(matches!(play(&revealed, 1, 1, Action::Flag), Err(InvalidErr::InvalidCoordinate(CoordinateErr::TileRevealed))));
+        assert_eq!(revealed.get_player(&1).points, 1);
+    }
+
+    #[test]
+    fn classic_won_without_flags() {
+        let game = reveal(&two_player_game(Rules::Classic), 1, 2, 2);
+
+        assert_eq!(game.status, GameStatus::Won);
+    }
+
+    #[test]
+    fn elimination_knocks_out_player() {
+        let game = reveal(&two_player_game(Rules::Elimination), 1, 0, 0);
+
+        assert_eq!(game.status, GameStatus::Continue);
+        assert!(game.is_eliminated(&1));
+        assert_eq!(game.get_winners(), vec![game.get_player(&2)]);
+    }
+
+    #[test]
+    fn elimination_lost_when_everyone_is_out() {
+        // a 1x2 board keeps the mine hidden for the second player
+        let game = Game::from_board(Board::new_test(2, 1, HashSet::from([Coordinate{ x: 0, y: 0 }])))
+            .with_rules(Rules::Elimination)
+            .add_player(Player::new_with_id(1, "hyeyoung"));
+        let game = reveal(&game, 1, 0, 0);
+
+        assert_eq!(game.status, GameStatus::Lost(1, Coordinate{ x: 0, y: 0 }));
+        assert!(game.get_winners().is_empty());
+    }
+
+    #[test]
+    fn points_only_continues_after_mine() {
+        let game = reveal(&two_player_game(Rules::PointsOnly), 1, 0, 0);
+
+        assert_eq!(game.status, GameStatus::Continue);
+        assert_eq!(game.get_player(&1).points, -10);
+
+        let game = reveal(&game, 2, 2, 2);
+        assert_eq!(game.status, GameStatus::Won);
+    }
+
+    #[test]
+    fn players_take_turns_by_id() {
+        let game = two_player_game(Rules::PointsOnly);
+        assert_eq!(game.current_player().unwrap().id, 1);
+
+        let game = reveal(&game, 1, 1, 1);
+        assert_eq!(game.current_player().unwrap().id, 2);
+
+        let game = reveal(&game, 2, 2, 2);
+        assert_eq!(game.current_player().unwrap().id, 1);
+    }
+
+    #[test]
+    fn hint_costs_points_and_keeps_the_turn() {
+        let game = two_player_game(Rules::PointsOnly);
+        let hint = PlayerAction{ player_id: 1, coordinate: Coordinate{ x: 1, y: 1 }, action: Action::Hint };
+
+        let hinted_game = game.update(&hint).unwrap();
+        assert_eq!(hinted_game.get_player(&1).points, -HINT_COST);
+        assert_eq!(hinted_game.current_player().unwrap().id, 1);
+        assert_eq!(hinted_game.board.get_tile(&hint.coordinate), &TileStatus::Hidden);
+        assert_eq!(hinted_game.progress(&1).moves, 0);
+    }
+
+    #[test]
+    fn hint_is_free_for_a_single_player() {
+        let game = Game::from_board(Board::new_test(3, 3, HashSet::from([Coordinate{ x: 0, y: 0 }])))
+            .with_rules(Rules::Classic)
+            .add_player(Player::new_with_id(1, "hyeyoung"));
+        let hinted_game = game.update(&PlayerAction{ player_id: 1, coordinate: Coordinate{ x: 2, y: 2 }, action: Action::Hint }).unwrap();
+
+        assert!(!game.is_scored());
+        assert_eq!(hinted_game.get_player(&1).points, 0);
+    }
+
+    #[test]
+    fn flag_before_first_reveal_is_rejected() {
+        let game = Game::from_board(Board::new_deferred(9, 9, Difficulty::Hard, 7, crate::core::board::FirstMove::SafeOpening))
+            .add_player(Player::new_with_id(1, "hyeyoung"));
+        let player_action = PlayerAction{ player_id: 1, coordinate: Coordinate{ x: 4, y: 4 }, action: Action::Flag };
+
+        assert!(matches!(game.update(&player_action), Err(InvalidErr::NoMinesYet)));
+        assert_eq!(game.get_player(&1).points, 0);
+    }
+
+    #[test]
+    fn out_of_turn_action_is_rejected() {
+        let game = two_player_game(Rules::PointsOnly);
+        let player_action = PlayerAction{ player_id: 2, coordinate: Coordinate{ x: 1, y: 1 }, action: Action::Reveal };
+
+        assert!(matches!(game.update(&player_action), Err(InvalidErr::NotYourTurn)));
+    }
+
+    #[test]
+    fn unknown_player_is_rejected() {
+        let game = two_player_game(Rules::PointsOnly);
+        let player_action = PlayerAction{ player_id: 3, coordinate: Coordinate{ x: 1, y: 1 }, action: Action::Reveal };
+
+        assert!(matches!(game.update(&player_action), Err(InvalidErr::InvalidPlayer)));
+    }
+
+    #[test]
+    fn eliminated_player_loses_turns() {
+        let game = two_player_game(Rules::Elimination)
+            .add_player(Player::new_with_id(3, "william"));
+        let game = reveal(&reveal(&game, 1, 0, 0), 2, 1, 1);
+
+        let game = reveal(&game, 3, 2, 1);
+        assert_eq!(game.current_player().unwrap().id, 2);
+    }
+
+    fn race_game() -> Game {
+        two_player_game(Rules::PointsOnly).with_turn_order(TurnOrder::Simultaneous, false)
+    }
+
+    #[test]
+    fn race_players_move_in_any_order() {
+        let game = reveal(&race_game(), 2, 1, 1);
+        let game = reveal(&game, 2, 1, 0);
+        let game = reveal(&game, 1, 0, 1);
+
+        assert_eq!(game.current_player(), None);
+        assert_eq!(game.get_player(&1).points, 1);
+        assert_eq!(game.get_player(&2).points, 2);
+    }
+
+    #[test]
+    fn race_first_move_wins_the_tile() {
+        // both players chose (2,2) on the same board; player 1's move arrived first
+        let game = reveal(&race_game(), 1, 2, 2);
+        let late_move = PlayerAction{ player_id: 2, coordinate: Coordinate{ x: 2, y: 2 }, action: Action::Reveal };
+
+        assert!(matches!(game.update(&late_move), Err(InvalidErr::TileTaken)));
+        assert_eq!(game.get_player(&2).points, 0);
+    }
+
+    #[test]
+    fn race_flag_on_revealed_tile_is_too_late() {
+        let game = reveal(&race_game(), 1, 1, 1);
+        let late_move = PlayerAction{ player_id: 2, coordinate: Coordinate{ x: 1, y: 1 }, action: Action::Flag };
+
+        assert!(matches!(game.update(&late_move), Err(InvalidErr::TileTaken)));
+    }
+
+    #[test]
+    fn race_moves_that_never_fit_are_invalid() {
+        let game = race_game();
+        let unflag = PlayerAction{ player_id: 1, coordinate: Coordinate{ x: 1, y: 1 }, action: Action::Unflag };
+        let chord = PlayerAction{ player_id: 1, coordinate: Coordinate{ x: 1, y: 1 }, action: Action::Chord };
+
+        assert!(matches!(game.update(&unflag), Err(InvalidErr::InvalidAction)));
+        assert!(matches!(game.update(&chord), Err(InvalidErr::InvalidAction)));
+    }
+
+    #[test]
+    fn parallel_boards_are_separate() {
+        let game = two_player_game(Rules::Classic).with_mode(Mode::ParallelBoards);
+        let game = reveal(&game, 1, 1, 1);
+
+        assert_eq!(game.player_board(&1).get_tile(&Coordinate{ x: 1, y: 1 }), &TileStatus::Revealed(Tile::Hint(1)));
+        assert_eq!(game.player_board(&2).get_tile(&Coordinate{ x: 1, y: 1 }), &TileStatus::Hidden);
+        assert_eq!(game.board.get_tile(&Coordinate{ x: 1, y: 1 }), &TileStatus::Hidden);
+    }
+
+    #[test]
+    fn parallel_first_to_clear_wins() {
+        let game = two_player_game(Rules::Classic).with_mode(Mode::ParallelBoards);
+
+        // player 1 opens every safe tile of their board at once
+        let game = reveal(&game, 1, 2, 2);
+        assert_eq!(game.progress(&1).completed_at, Some(1));
+        assert_eq!(game.status, GameStatus::Continue);
+        assert_eq!(game.current_player().unwrap().id, 2);
+
+        // a mine ends player 2's game, and with it the whole game
+        let game = reveal(&game, 2, 0, 0);
+        assert_eq!(game.progress(&2).mistakes, 1);
+        assert_eq!(game.status, GameStatus::Won);
+        assert_eq!(game.get_winners(), vec![game.get_player(&1)]);
+        assert_eq!(game.ranking(), vec![game.get_player(&1), game.get_player(&2)]);
+    }
+
+    #[test]
+    fn parallel_lost_when_nobody_clears() {
+        let game = two_player_game(Rules::Classic).with_mode(Mode::ParallelBoards);
+        let game = reveal(&reveal(&game, 1, 0, 0), 2, 0, 0);
+
+        assert_eq!(game.status, GameStatus::Lost(2, Coordinate{ x: 0, y: 0 }));
+        assert!(game.get_winners().is_empty());
+    }
+
+    #[test]
+    fn parallel_ranking_without_clearing() {
+        // ranked by points; the flag on a safe tile costs player 1 a point and counts as a mistake
+        let game = two_player_game(Rules::PointsOnly).with_mode(Mode::ParallelBoards);
+        let game = game.update(&PlayerAction{ player_id: 1, coordinate: Coordinate{ x: 1, y: 1 }, action: Action::Flag }).unwrap();
+        let game = reveal(&game, 2, 1, 1);
+
+        assert_eq!(game.progress(&1).mistakes, 1);
+        assert_eq!(game.ranking(), vec![game.get_player(&2), game.get_player(&1)]);
+        assert_eq!(game.get_winners(), vec![game.get_player(&2)]);
+    }
+
+    #[test]
+    fn forfeit_passes_turn() {
+        let game = two_player_game(Rules::PointsOnly).forfeit(&1);
+
+        assert_eq!(game.current_player().unwrap().id, 2);
+        assert_eq!(game.get_winners(), vec![game.get_player(&2)]);
+    }
+
+    #[test]
+    fn last_opponent_forfeits() {
+        // everyone else is out, so the game is lost on the mine that knocked player 1 out
+        let game = reveal(&two_player_game(Rules::Elimination), 1, 0, 0).forfeit(&2);
+
+        assert_eq!(game.status, GameStatus::Lost(1, Coordinate{ x: 0, y: 0 }));
+        assert!(game.active_players().is_empty());
+
+        // with parallel boards, the player who already cleared theirs wins
+        let game = reveal(&two_player_game(Rules::Classic).with_mode(Mode::ParallelBoards), 1, 2, 2).forfeit(&2);
+
+        assert_eq!(game.status, GameStatus::Won);
+        assert_eq!(game.get_winners(), vec![game.get_player(&1)]);
+    }
+
+    #[test]
+    fn extra_turn_on_flagged_mine() {
+        let game = two_player_game(Rules::PointsOnly)
+            .with_turn_order(TurnOrder::RoundRobin, true)
+            .update(&PlayerAction{ player_id: 1, coordinate: Coordinate{ x: 0, y: 0 }, action: Action::Flag })
+            .unwrap();
+        assert_eq!(game.current_player().unwrap().id, 1);
+
+        // a flag on a safe tile reveals it, which does not earn another turn
+        let game = game.update(&PlayerAction{ player_id: 1, coordinate: Coordinate{ x: 2, y: 1 }, action: Action::Flag }).unwrap();
+        assert_eq!(game.current_player().unwrap().id, 2);
+    }
+
+    #[test]
+    fn shuffled_turns_follow_seed() {
+        let game = || Game::new(4, 4, Difficulty::Easy, 7)
+            .add_player(Player::new_with_id(1, "hyeyoung"))
+            .add_player(Player::new_with_id(2, "charlie"))
+            .add_player(Player::new_with_id(3, "william"))
+            .with_turn_order(TurnOrder::ShuffledRounds, false);
+
+        assert_eq!(game().turns, game().turns);
+    }
+
     #[test]
     fn get_winner() {
-        let game = Game::new(3, 3, Difficulty::Easy);
+        let game = Game::new(3, 3, Difficulty::Easy, 0);
         let player_1 = Player::new_with_id(1, "hyeyoung");
         let player_2 = Player::new_with_id(2, "charlie");
         let player_3 = Player::new_with_id(3, "william");
@@ -192,7 +913,7 @@ mod tests {
 
     #[test]
     fn get_winners() {
-        let game = Game::new(3, 3, Difficulty::Easy);
+        let game = Game::new(3, 3, Difficulty::Easy, 0);
         let player_1 = Player::new_with_id(1, "hyeyoung");
         let player_2 = Player::new_with_id(2, "charlie");
         let player_3 = Player::new_with_id(3, "william");
@@ -0,0 +1,234 @@
+// Hints: one tile a player can work out from what they see, and why
+//
+// Rules that look at one or two hints are explained hint by hint. Anything deeper is put down
+// to the solver, and when nothing is certain the hint points at the least likely mine.
+
+use std::collections::HashSet;
+
+use crate::core::board::{Coordinate, Tile, TileStatus};
+use crate::core::probability::mine_probabilities;
+use crate::core::solver::{self, constraint_at, deduce, global_constraint, settled, Constraint, Deductions, Rule};
+use crate::core::view::PlayerView;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Verdict {
+    Safe,
+    Mine,
+    Guess(f64), // nothing is certain; the chance that this tile is a mine
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hint {
+    pub coordinate: Coordinate,
+    pub verdict: Verdict,
+    pub reason: String,
+}
+
+// A safe tile if the player can find one, otherwise a mine, otherwise the best guess
+// None once no tile is hidden
+pub fn hint(view: &PlayerView) -> Option<Hint> {
+    let known = Deductions::default();
+    let mut hints: Vec<(Coordinate, Constraint)> = view.iter()
+        .filter_map(|(coordinate, _)| constraint_at(view, coordinate, &known).map(|constraint| (*coordinate, constraint)))
+        .collect();
+    hints.sort_by_key(|(coordinate, _)| (coordinate.y, coordinate.x));
+
+    let mut found = single_hints(view, &hints);
+    found.extend(pairs_of_hints(&hints));
+    found.extend(mine_count(view));
+
+    // the solver is only asked when the simple rules find nothing safe
+    if !found.iter().any(|hint| hint.verdict == Verdict::Safe) {
+        found.extend(solver_deductions(view));
+    }
+
+    match found.iter().position(|hint| hint.verdict == Verdict::Safe) {
+        Some(i) => Some(found.swap_remove(i)),
+        None if !found.is_empty() => Some(found.swap_remove(0)),
+        None => best_guess(view),
+    }
+}
+
+// A hint that already touches all its mines, or has just enough hidden tiles left for them
+fn single_hints(view: &PlayerView, hints: &[(Coordinate, Constraint)]) -> Vec<Hint> {
+    hints.iter()
+        .flat_map(|(coordinate, constraint)| {
+            let TileStatus::Revealed(Tile::Hint(number)) = view.get_tile(coordinate) else { return Vec::new() };
+            let known_mines = *number as u32 - constraint.mines;
+            let has = match known_mines {
+                0 => format!("{} has hint {}", at(coordinate), number),
+                _ => format!("{} has hint {} and already touches {}", at(coordinate), number, count(known_mines, "known mine")),
+            };
+
+            verdicts(&settled(constraint), |tile, verdict| match verdict {
+                Verdict::Mine if constraint.coordinates.len() == 1 => format!("{}, and {} is the only hidden tile left around it", has, at(tile)),
+                Verdict::Mine => format!("{}, and only {} hidden tiles are left around it", has, constraint.coordinates.len()),
+                _ => has.clone(),
+            })
+        })
+        .collect()
+}
+
+// Two hints next to each other: one's hidden tiles all touch the other, or they share some
+fn pairs_of_hints(hints: &[(Coordinate, Constraint)]) -> Vec<Hint> {
+    let mut found = Vec::new();
+
+    for (i, (a, a_constraint)) in hints.iter().enumerate() {
+        for (b, b_constraint) in &hints[i + 1..] {
+            let pair = [a_constraint.clone(), b_constraint.clone()];
+
+            found.extend(verdicts(&solver::subset(&pair), |_, verdict| {
+                let ((small, small_constraint), (big, big_constraint)) = match a_constraint.coordinates.is_subset(&b_constraint.coordinates) {
+                    true => ((a, a_constraint), (b, b_constraint)),
+                    false => ((b, b_constraint), (a, a_constraint)),
+                };
+                let needs = format!("every hidden tile around {} is next to {} too, and {} needs {} while {} needs {}",
+                    at(small), at(big), at(small), count(small_constraint.mines, "more mine"), at(big), big_constraint.mines);
+
+                match verdict {
+                    Verdict::Safe => format!("{}, so the rest of the tiles around {} are safe", needs, at(big)),
+                    _ => format!("{}, so the rest of the tiles around {} are mines", needs, at(big)),
+                }
+            }));
+
+            found.extend(verdicts(&solver::overlap(&pair), |tile, verdict| {
+                // the tile is on the side of the hint that was settled
+                let ((a, a_constraint), (b, b_constraint)) = match a_constraint.coordinates.contains(tile) {
+                    true => ((a, a_constraint), (b, b_constraint)),
+                    false => ((b, b_constraint), (a, a_constraint)),
+                };
+                let shared = a_constraint.coordinates.intersection(&b_constraint.coordinates).count() as u32;
+                let only_b = b_constraint.coordinates.len() as u32 - shared;
+
+                match verdict {
+                    Verdict::Safe => format!("{} needs {} but has only {} away from {}, \
+                        so the tiles they share hold every mine {} needs", at(b), count(b_constraint.mines, "more mine"),
+                        count(only_b, "hidden tile"), at(a), at(a)),
+                    _ => format!("{} needs {} but the tiles it shares with {} hold at most {}, so the rest are mines",
+                        at(a), count(a_constraint.mines, "more mine"), at(b), shared.min(b_constraint.mines)),
+                }
+            }));
+        }
+    }
+
+    found
+}
+
+// Every mine is accounted for, or every unknown tile must be one
+fn mine_count(view: &PlayerView) -> Vec<Hint> {
+    let constraint = global_constraint(view, &Deductions::default());
+
+    verdicts(&settled(&constraint), |_, verdict| match verdict {
+        Verdict::Mine => "there are as many hidden tiles left as mines left".to_string(),
+        _ => format!("all {} mines are already known", view.num_mines),
+    })
+}
+
+fn solver_deductions(view: &PlayerView) -> Vec<Hint> {
+    let deductions = deduce(view);
+
+    verdicts(&deductions, |tile, verdict| match (deductions.rules.get(tile), verdict) {
+        (Some(Rule::Enumeration), Verdict::Mine) => "every way the mines can lie around the hints puts one there".to_string(),
+        (Some(Rule::Enumeration), _) => "every way the mines can lie around the hints leaves it empty".to_string(),
+        _ => "it follows from the hints once the tiles you can already work out are settled".to_string(),
+    })
+}
+
+fn best_guess(view: &PlayerView) -> Option<Hint> {
+    mine_probabilities(view).into_iter()
+        .min_by(|(a, p), (b, q)| p.total_cmp(q).then((a.y, a.x).cmp(&(b.y, b.x))))
+        .map(|(coordinate, probability)| Hint {
+            coordinate,
+            verdict: Verdict::Guess(probability),
+            reason: "no tile is certain, and this one is the least likely to be a mine".to_string(),
+        })
+}
+
+// One hint per tile `deductions` settled, safe tiles first, each row by row
+fn verdicts(deductions: &Deductions, reason: impl Fn(&Coordinate, Verdict) -> String) -> Vec<Hint> {
+    let sorted = |tiles: &HashSet<Coordinate>| {
+        let mut tiles: Vec<Coordinate> = tiles.iter().copied().collect();
+        tiles.sort_by_key(|coordinate| (coordinate.y, coordinate.x));
+        tiles
+    };
+
+    sorted(&deductions.safe).into_iter().map(|tile| (tile, Verdict::Safe))
+        .chain(sorted(&deductions.mines).into_iter().map(|tile| (tile, Verdict::Mine)))
+        .map(|(coordinate, verdict)| Hint { coordinate, verdict, reason: reason(&coordinate, verdict) })
+        .collect()
+}
+
+fn at(coordinate: &Coordinate) -> String {
+    format!("({},{})", coordinate.x, coordinate.y)
+}
+
+// e.g. 1 mine, 2 mines
+fn count(count: u32, thing: &str) -> String {
+    if count == 1 { format!("1 {}", thing) } else { format!("{} {}s", count, thing) }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::board::Board;
+    use crate::core::player::{Action, PlayerAction};
+
+    fn reveal(board: &Board, x: u32, y: u32) -> Board {
+        board.update(&PlayerAction{ player_id: 1, coordinate: Coordinate{ x, y }, action: Action::Reveal })
+    }
+
+    #[test]
+    fn only_hidden_neighbor() {
+        // ? 1 .
+        // 1 1 .
+        // . . .
+        let board = Board::new_test(3, 3, HashSet::from([Coordinate{ x: 0, y: 0 }]));
+        let hint = hint(&reveal(&board, 2, 2).view()).unwrap();
+
+        assert_eq!(hint, Hint {
+            coordinate: Coordinate{ x: 0, y: 0 },
+            verdict: Verdict::Mine,
+            reason: "(1,0) has hint 1, and (0,0) is the only hidden tile left around it".to_string(),
+        });
+    }
+
+    #[test]
+    fn safe_tiles_come_first() {
+        // ! 1 ?    the flag settles the 1, so its other neighbors are safe
+        // ? ? ?
+        let board = Board::new_test(3, 2, HashSet::from([Coordinate{ x: 0, y: 0 }]));
+        let board = reveal(&board, 1, 0)
+            .update(&PlayerAction{ player_id: 1, coordinate: Coordinate{ x: 0, y: 0 }, action: Action::Flag });
+        let hint = hint(&board.view()).unwrap();
+
+        assert_eq!(hint.verdict, Verdict::Safe);
+        assert_eq!(hint.coordinate, Coordinate{ x: 2, y: 0 });
+        assert_eq!(hint.reason, "(1,0) has hint 1 and already touches 1 known mine");
+    }
+
+    #[test]
+    fn guess_when_nothing_is_certain() {
+        let board = Board::new_test(2, 2, HashSet::from([Coordinate{ x: 0, y: 1 }]));
+        let hint = hint(&reveal(&reveal(&board, 0, 0), 1, 0).view()).unwrap();
+
+        assert_eq!(hint.verdict, Verdict::Guess(0.5));
+    }
+
+    #[test]
+    fn hints_are_never_wrong() {
+        for seed in 0..200 {
+            let board = Board::new(8, 8, crate::core::game::Difficulty::Hard, seed);
+            let Some(start) = board.iter().map(|(c, _)| *c).find(|c| !board.is_mine(c) && board.get_hint(c) == 0) else { continue };
+            let board = reveal(&board, start.x, start.y);
+
+            if let Some(hint) = hint(&board.view()) {
+                match hint.verdict {
+                    Verdict::Safe => assert!(!board.is_mine(&hint.coordinate), "seed {}: {:?}", seed, hint),
+                    Verdict::Mine => assert!(board.is_mine(&hint.coordinate), "seed {}: {:?}", seed, hint),
+                    Verdict::Guess(_) => {},
+                }
+            }
+        }
+    }
+}
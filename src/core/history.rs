@@ -0,0 +1,177 @@
+// Undo/redo on top of Game
+//
+// Every Game shares its board map (im::HashMap) and mines (Rc) with the games before it,
+// so keeping all past games costs little more than the tiles each move changed.
+
+use im::Vector;
+
+use crate::core::game::Game;
+use crate::core::player::{PlayerAction, PlayerId};
+use crate::core::validation::InvalidErr;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UndoPolicy {
+    Disabled,       // scored games
+    Free,           // practice
+    Penalized(i32), // undoing costs the player this many points, even if the move is redone
+}
+
+impl UndoPolicy {
+    // What one undo costs the player who asks for it
+    pub fn penalty(&self) -> i32 {
+        match self {
+            UndoPolicy::Penalized(points) => *points,
+            UndoPolicy::Disabled | UndoPolicy::Free => 0,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct GameHistory {
+    past: Vector<Game>,
+    present: Game,
+    future: Vector<Game>, // games undone, most recently undone last
+    pub policy: UndoPolicy,
+}
+
+impl GameHistory {
+    pub fn new(game: Game, policy: UndoPolicy) -> GameHistory {
+        GameHistory { past: Vector::new(), present: game, future: Vector::new(), policy }
+    }
+
+    pub fn game(&self) -> &Game {
+        &self.present
+    }
+
+    pub fn into_game(self) -> Game {
+        self.present
+    }
+
+    // A new move makes the undone games unreachable
+    pub fn update(&self, player_action: &PlayerAction) -> Result<GameHistory, InvalidErr> {
+        let present = self.present.update(player_action)?;
+
+        let mut past = self.past.clone();
+        past.push_back(self.present.clone());
+
+        Ok(GameHistory {
+            past,
+            present,
+            future: Vector::new(),
+            policy: self.policy,
+        })
+    }
+
+    pub fn undo(&self, player_id: &PlayerId) -> Result<GameHistory, InvalidErr> {
+        if self.policy == UndoPolicy::Disabled {
+            return Err(InvalidErr::UndoDisabled);
+        }
+        let penalty = self.policy.penalty();
+
+        let mut past = self.past.clone();
+        let previous = past.pop_back().ok_or(InvalidErr::NothingToUndo)?;
+
+        let mut future = self.future.clone();
+        future.push_back(self.present.clone());
+
+        // the penalty follows every game the player can still reach, with undo as well as redo
+        let penalize = |game: Game| if penalty == 0 { game } else { game.subtract_points(player_id, penalty) };
+
+        Ok(GameHistory {
+            past: past.into_iter().map(penalize).collect(),
+            present: penalize(previous),
+            future: future.into_iter().map(penalize).collect(),
+            policy: self.policy,
+        })
+    }
+
+    pub fn redo(&self) -> Result<GameHistory, InvalidErr> {
+        if self.policy == UndoPolicy::Disabled {
+            return Err(InvalidErr::UndoDisabled);
+        }
+
+        let mut future = self.future.clone();
+        let next = future.pop_back().ok_or(InvalidErr::NothingToRedo)?;
+
+        let mut past = self.past.clone();
+        past.push_back(self.present.clone());
+
+        Ok(GameHistory { past, present: next, future, policy: self.policy })
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+    use crate::core::board::{Board, Coordinate, TileStatus};
+    use crate::core::player::{Action, Player};
+
+    fn history(policy: UndoPolicy) -> GameHistory {
+        let game = Game::from_board(Board::new_test(3, 3, HashSet::from([Coordinate{ x: 0, y: 0 }])))
+            .add_player(Player::new_with_id(1, "hyeyoung"));
+
+        GameHistory::new(game, policy)
+            .update(&PlayerAction{ player_id: 1, coordinate: Coordinate{ x: 1, y: 1 }, action: Action::Reveal })
+            .unwrap()
+    }
+
+    #[test]
+    fn undo_and_redo() {
+        let history = history(UndoPolicy::Free);
+
+        let undone = history.undo(&1).unwrap();
+        assert_eq!(undone.game().board.get_tile(&Coordinate{ x: 1, y: 1 }), &TileStatus::Hidden);
+        assert_eq!(undone.game().get_player(&1).points, 0);
+
+        let redone = undone.redo().unwrap();
+        assert_ne!(redone.game().board.get_tile(&Coordinate{ x: 1, y: 1 }), &TileStatus::Hidden);
+        assert_eq!(redone.game().get_player(&1).points, 1);
+    }
+
+    #[test]
+    fn nothing_to_undo() {
+        let history = history(UndoPolicy::Free).undo(&1).unwrap();
+
+        assert!(matches!(history.undo(&1), Err(InvalidErr::NothingToUndo)));
+    }
+
+    #[test]
+    fn new_move_clears_redo() {
+        let history = history(UndoPolicy::Free)
+            .undo(&1).unwrap()
+            .update(&PlayerAction{ player_id: 1, coordinate: Coordinate{ x: 0, y: 0 }, action: Action::Flag }).unwrap();
+
+        assert!(matches!(history.redo(), Err(InvalidErr::NothingToRedo)));
+    }
+
+    #[test]
+    fn disabled_undo() {
+        let history = history(UndoPolicy::Disabled);
+
+        assert!(matches!(history.undo(&1), Err(InvalidErr::UndoDisabled)));
+        assert!(matches!(history.redo(), Err(InvalidErr::UndoDisabled)));
+    }
+
+    #[test]
+    fn penalized_undo_sticks_after_redo() {
+        let undone = history(UndoPolicy::Penalized(5)).undo(&1).unwrap();
+        assert_eq!(undone.game().get_player(&1).points, -5);
+
+        let redone = undone.redo().unwrap();
+        assert_eq!(redone.game().get_player(&1).points, 1 - 5);
+    }
+
+    #[test]
+    fn penalized_undos_add_up() {
+        let history = history(UndoPolicy::Penalized(5))
+            .update(&PlayerAction{ player_id: 1, coordinate: Coordinate{ x: 0, y: 0 }, action: Action::Flag }).unwrap();
+
+        let undone = history.undo(&1).unwrap().undo(&1).unwrap();
+        assert_eq!(undone.game().get_player(&1).points, -10);
+
+        let redone = undone.redo().unwrap().redo().unwrap();
+        assert_eq!(redone.game().get_player(&1).points, history.game().get_player(&1).points - 10);
+    }
+}
@@ -2,8 +2,19 @@ pub mod board;
 pub mod player;
 pub mod game;
 pub mod validation;
+pub mod solver;
+pub mod save;
+pub mod replay;
+pub mod history;
+pub mod view;
+pub mod turn;
+pub mod debug;
+pub mod probability;
+pub mod hint;
+pub mod controller;
 
 // Re-export commonly used items
 pub use board::Board;
 pub use player::Player;
-pub use game::Game;
\ No newline at end of file
+pub use game::Game;
+pub use view::PlayerView;
\ No newline at end of file
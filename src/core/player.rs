@@ -1,37 +1,41 @@
 use crate::core::board::Coordinate;
 
 use std::sync::atomic::{AtomicU32, Ordering};
+use serde::{Deserialize, Serialize};
 
 static NEXT_PLAYER_ID: AtomicU32 = AtomicU32::new(1);
 
 pub type PlayerId = u32;
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub struct PlayerAction {
     pub player_id: PlayerId,
     pub coordinate: Coordinate,
     pub action: Action, 
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct Player {
     pub id: PlayerId,
     pub name: String,
     pub points: i32,
 }
 
-#[derive(Debug, PartialEq, Clone, Copy)]
+#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
 pub enum Action{
     Reveal, // Revealing all in the hint = 0 case is always 3. Revealing a hint tile is 1.
     Flag,   // If flagged a non-mine tile, it reveals. In this case, even if the tile has hint = 0, it does not reveal all of its neighbors. The player gets a penalty point -1.
+    Unflag, // Only the player who placed a flag can take it back. Retracting a flag returns the 2 points it earned.
+    Chord,  // On a revealed hint with as many flagged neighbors as its number, reveals all other neighbors. Each neighbor scores as a Reveal.
+    Hint,   // Shows the player a safe tile or a mine; the board does not change. It costs points in scored games and does not pass the turn.
 }
 
 impl Player {
     pub fn new(name: String) -> Self {
         Player { 
             id: NEXT_PLAYER_ID.fetch_add(1, Ordering::Relaxed), 
-            name: name,
-            points: 0 
+            name,
+            points: 0,
         }
     }
 
@@ -0,0 +1,228 @@
+// Chance that each hidden tile is a mine, from what a player sees
+//
+// Tiles the solver settles are 0 or 1. The rest are split like the solver's enumeration: groups of tiles next to hints,
+// whose placements are counted, and the tiles away from hints, which share whatever mines are left.
+// Every way of combining them is weighted by the number of layouts it stands for.
+
+use std::collections::HashMap;
+
+use crate::core::board::{Coordinate, TileStatus};
+use crate::core::solver::{constraints, deduce, Frontier};
+use crate::core::view::PlayerView;
+
+// Exact, except that a group too big to enumerate (see solver::MAX_ENUMERATED_TILES)
+// is treated like the tiles away from hints
+pub fn mine_probabilities(view: &PlayerView) -> HashMap<Coordinate, f64> {
+    let known = deduce(view);
+    let frontier = Frontier::new(view, &known, &constraints(view, &known));
+
+    let (groups, too_big): (Vec<_>, Vec<_>) = frontier.groups.iter().partition(|group| group.placements.is_some());
+    let interior: Vec<Coordinate> = frontier.interior.iter()
+        .chain(too_big.iter().flat_map(|group| group.tiles.iter()))
+        .copied()
+        .collect();
+    let remaining_mines = frontier.remaining_mines as usize;
+
+    // how many placements each group has, by number of mines
+    let counts: Vec<Vec<f64>> = groups.iter()
+        .map(|group| {
+            let placements = group.placements.as_ref().expect("only enumerated groups are left");
+            let most = placements.keys().max().copied().unwrap_or(0);
+
+            (0..=most).map(|num_mines| placements.get(&num_mines).map_or(0.0, |(num_placements, _)| *num_placements as f64)).collect()
+        })
+        .collect();
+
+    // how many ways the mines left over fit on the interior, for each number of mines in the groups
+    let interior_ways = interior_ways(interior.len(), remaining_mines);
+    let weighted = |totals: &[f64], num_mines: usize| -> f64 {
+        totals.iter().enumerate()
+            .map(|(other_mines, ways)| ways * interior_ways.get(num_mines + other_mines).copied().unwrap_or(0.0))
+            .sum()
+    };
+
+    let all_groups = product(&counts, None);
+    let total_weight = weighted(&all_groups, 0);
+
+    let mut probabilities: HashMap<Coordinate, f64> = HashMap::new();
+
+    for (i, group) in groups.iter().enumerate() {
+        let placements = group.placements.as_ref().expect("only enumerated groups are left");
+        let other_groups = product(&counts, Some(i));
+
+        for (t, tile) in group.tiles.iter().enumerate() {
+            let weight: f64 = placements.iter()
+                .map(|(num_mines, (_, mine_counts))| mine_counts[t] as f64 * weighted(&other_groups, *num_mines as usize))
+                .sum();
+
+            probabilities.insert(*tile, weight / total_weight);
+        }
+    }
+
+    // every interior tile is as likely as any other to hold one of the mines left over
+    let interior_mines: f64 = all_groups.iter().enumerate()
+        .map(|(group_mines, ways)| {
+            let left_over = remaining_mines.saturating_sub(group_mines) as f64;
+            ways * interior_ways.get(group_mines).copied().unwrap_or(0.0) * left_over
+        })
+        .sum();
+    for tile in &interior {
+        probabilities.insert(*tile, interior_mines / total_weight / interior.len() as f64);
+    }
+
+    probabilities.extend(known.safe.iter().map(|tile| (*tile, 0.0)));
+    probabilities.extend(known.mines.iter().map(|tile| (*tile, 1.0)));
+    probabilities.retain(|tile, _| matches!(view.get_tile(tile), TileStatus::Hidden));
+
+    // only a board that contradicts itself has no layout at all
+    if total_weight == 0.0 {
+        probabilities.values_mut().filter(|p| p.is_nan()).for_each(|p| *p = 0.0);
+    }
+
+    probabilities
+}
+
+// Multiplies the groups' counts as polynomials in the number of mines, leaving out group `except`
+fn product(counts: &[Vec<f64>], except: Option<usize>) -> Vec<f64> {
+    counts.iter().enumerate()
+        .filter(|(i, _)| Some(*i) != except)
+        .fold(vec![1.0], |totals, (_, group)| {
+            let mut product = vec![0.0; totals.len() + group.len() - 1];
+
+            for (a, x) in totals.iter().enumerate() {
+                for (b, y) in group.iter().enumerate() {
+                    product[a + b] += x * y;
+                }
+            }
+
+            product
+        })
+}
+
+// C(num_tiles, remaining_mines - group_mines) for every number of group mines,
+// scaled by a common factor so that large boards do not overflow
+fn interior_ways(num_tiles: usize, remaining_mines: usize) -> Vec<f64> {
+    let ln_ways: Vec<Option<f64>> = (0..=remaining_mines)
+        .map(|group_mines| {
+            let left_over = remaining_mines - group_mines;
+            (left_over <= num_tiles).then(|| ln_choose(num_tiles, left_over))
+        })
+        .collect();
+
+    let largest = ln_ways.iter().flatten().copied().fold(f64::NEG_INFINITY, f64::max);
+
+    ln_ways.iter()
+        .map(|ln_ways| ln_ways.map_or(0.0, |ln_ways| (ln_ways - largest).exp()))
+        .collect()
+}
+
+fn ln_choose(n: usize, k: usize) -> f64 {
+    (1..=k).map(|i| ((n - k + i) as f64).ln() - (i as f64).ln()).sum()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+    use crate::core::board::{Board, Tile};
+    use crate::core::game::Difficulty;
+    use crate::core::player::{Action, PlayerAction};
+
+    fn reveal(board: &Board, x: u32, y: u32) -> Board {
+        board.update(&PlayerAction{ player_id: 1, coordinate: Coordinate{ x, y }, action: Action::Reveal })
+    }
+
+    // Counts every layout that fits the view, one by one
+    fn brute_force(view: &PlayerView) -> HashMap<Coordinate, f64> {
+        let hidden: Vec<Coordinate> = view.iter()
+            .filter(|(_, tile_status)| matches!(tile_status, TileStatus::Hidden))
+            .map(|(coordinate, _)| *coordinate)
+            .collect();
+        let mut mine_counts = vec![0.0; hidden.len()];
+        let mut num_layouts = 0.0;
+
+        for layout in 0..(1u32 << hidden.len()) {
+            if layout.count_ones() != view.num_mines {
+                continue;
+            }
+            let is_mine = |coordinate: &Coordinate| hidden.iter().position(|c| c == coordinate).is_some_and(|i| layout & (1 << i) != 0);
+
+            let fits = view.iter().all(|(coordinate, tile_status)| match tile_status {
+                TileStatus::Revealed(Tile::Hint(hint)) => {
+                    view.neighboring_coordinates(coordinate).iter().filter(|c| is_mine(c)).count() as i8 == *hint
+                },
+                _ => true,
+            });
+
+            if fits {
+                num_layouts += 1.0;
+                for (i, coordinate) in hidden.iter().enumerate() {
+                    mine_counts[i] += is_mine(coordinate) as u32 as f64;
+                }
+            }
+        }
+
+        hidden.into_iter().zip(mine_counts).map(|(coordinate, count)| (coordinate, count / num_layouts)).collect()
+    }
+
+    #[test]
+    fn fifty_fifty() {
+        // 1 1
+        // ? ?
+        let board = Board::new_test(2, 2, HashSet::from([Coordinate{ x: 0, y: 1 }]));
+        let board = reveal(&reveal(&board, 0, 0), 1, 0);
+
+        let probabilities = mine_probabilities(&board.view());
+
+        assert_eq!(probabilities.len(), 2);
+        assert!(probabilities.values().all(|p| (p - 0.5).abs() < 1e-9));
+    }
+
+    #[test]
+    fn frontier_and_interior() {
+        // ? 1 ? ? ?    one of the five tiles around the 1 is a mine,
+        // ? ? ? ? ?    so the four tiles away from it share the other two
+        let board = Board::new_test(5, 2, HashSet::from([Coordinate{ x: 0, y: 0 }, Coordinate{ x: 4, y: 0 }, Coordinate{ x: 3, y: 1 }]));
+        let probabilities = mine_probabilities(&reveal(&board, 1, 0).view());
+
+        for (coordinate, p) in probabilities {
+            let expected = if coordinate.x < 3 { 0.2 } else { 0.5 };
+            assert!((p - expected).abs() < 1e-9, "{:?}: {} instead of {}", coordinate, p, expected);
+        }
+    }
+
+    #[test]
+    fn matches_brute_force() {
+        for seed in 0..200 {
+            let board = Board::new(4, 3, Difficulty::Custom { mines: 3 }, seed);
+
+            let revealed = board.iter()
+                .map(|(coordinate, _)| *coordinate)
+                .filter(|coordinate| !board.is_mine(coordinate) && (seed >> ((coordinate.x + coordinate.y * 4) % 7)) & 1 == 1)
+                .fold(board.clone(), |board, coordinate| reveal(&board, coordinate.x, coordinate.y));
+            let view = revealed.view();
+
+            let probabilities = mine_probabilities(&view);
+            let expected = brute_force(&view);
+
+            assert_eq!(probabilities.len(), expected.len());
+            for (coordinate, p) in expected {
+                assert!((probabilities[&coordinate] - p).abs() < 1e-9, "seed {}, {:?}: {} instead of {}", seed, coordinate, probabilities[&coordinate], p);
+            }
+        }
+    }
+
+    #[test]
+    fn large_board_does_not_overflow() {
+        let board = Board::new(30, 30, Difficulty::Density(0.3), 5);
+        let safe = board.iter().map(|(c, _)| *c).find(|c| !board.is_mine(c)).unwrap();
+        let view = reveal(&board, safe.x, safe.y).view();
+
+        let probabilities = mine_probabilities(&view);
+        let expected_mines: f64 = probabilities.values().sum();
+
+        assert!(probabilities.values().all(|p| p.is_finite() && (0.0..=1.0).contains(p)));
+        assert!((expected_mines - view.num_mines as f64).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,184 @@
+// Move log: every action of a game, replayable from the starting state
+
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::core::game::Game;
+use crate::core::player::{PlayerAction, PlayerId};
+use crate::core::save::{LoadErr, SavedGame};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedMove {
+    pub timestamp_ms: u64, // milliseconds since the Unix epoch
+    pub player_action: PlayerAction,
+}
+
+// Game::update is pure, so the starting state (which holds the seed and, once placed, the mines)
+// and the list of actions are enough to rebuild every intermediate game
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReplayLog {
+    pub initial: SavedGame,
+    pub moves: Vec<RecordedMove>,
+    #[serde(default)]
+    pub penalties: Vec<(PlayerId, i32)>, // undo penalties, which follow every game back to the first
+    #[serde(skip)]
+    undone: Vec<RecordedMove>, // moves taken back with undo, most recent last
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as u64)
+        .unwrap_or(0) // a clock set before 1970 only loses the timestamps
+}
+
+impl ReplayLog {
+    pub fn new(game: &Game) -> ReplayLog {
+        ReplayLog { initial: SavedGame::from_game(game), moves: Vec::new(), penalties: Vec::new(), undone: Vec::new() }
+    }
+
+    pub fn record(&mut self, player_action: &PlayerAction) {
+        self.moves.push(RecordedMove { timestamp_ms: now_ms(), player_action: *player_action });
+        self.undone.clear();
+    }
+
+    // Keeps the log in step with GameHistory, so that it replays the game as it was finally played
+    // `penalty` is what the undo cost `player_id`, as given by the UndoPolicy
+    pub fn undo(&mut self, player_id: &PlayerId, penalty: i32) {
+        if let Some(recorded_move) = self.moves.pop() {
+            self.undone.push(recorded_move);
+        }
+        if penalty != 0 {
+            self.penalties.push((*player_id, penalty));
+        }
+    }
+
+    pub fn redo(&mut self) {
+        if let Some(recorded_move) = self.undone.pop() {
+            self.moves.push(recorded_move);
+        }
+    }
+
+    // Yields the starting game, then the game after each recorded move
+    // Stops early at a move the game rejects, e.g. one edited into the file out of turn
+    pub fn replay(&self) -> Result<impl Iterator<Item = Game> + '_, LoadErr> {
+        // a penalty for a player who is not in the game was edited into the file, and is left out
+        let initial_game = self.penalties.iter()
+            .fold(self.initial.clone().into_game()?, |game, (player_id, points)| {
+                if game.players.contains_key(player_id) { game.subtract_points(player_id, *points) } else { game }
+            });
+        let mut moves = self.moves.iter();
+
+        Ok(std::iter::successors(Some(initial_game), move |game| {
+            moves.next().and_then(|recorded_move| game.update(&recorded_move.player_action).ok())
+        }))
+    }
+
+    // The game after `step` moves
+    pub fn game_at(&self, step: usize) -> Result<Option<Game>, LoadErr> {
+        Ok(self.replay()?.nth(step))
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let json = serde_json::to_string_pretty(self).expect("a replay log should always serialize");
+        fs::write(path, json)
+    }
+
+    pub fn load(path: &Path) -> Result<ReplayLog, LoadErr> {
+        Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::board::{Board, Coordinate, FirstMove, TileStatus};
+    use crate::core::game::Difficulty;
+    use crate::core::history::{GameHistory, UndoPolicy};
+    use crate::core::player::{Action, Player};
+
+    fn play(game: Game, log: &mut ReplayLog, player_actions: &[PlayerAction]) -> Game {
+        player_actions.iter().fold(game, |game, player_action| {
+            log.record(player_action);
+            game.update(player_action).unwrap()
+        })
+    }
+
+    #[test]
+    fn replay_rebuilds_every_step() {
+        // mines are placed by the first move, so the log only knows the seed
+        let game = Game::from_board(Board::new_deferred(6, 6, Difficulty::Medium, 11, FirstMove::SafeOpening))
+            .add_player(Player::new_with_id(1, "hyeyoung"));
+        let mut log = ReplayLog::new(&game);
+
+        let player_actions = [
+            PlayerAction{ player_id: 1, coordinate: Coordinate{ x: 2, y: 2 }, action: Action::Reveal },
+            PlayerAction{ player_id: 1, coordinate: Coordinate{ x: 0, y: 5 }, action: Action::Reveal },
+        ];
+        let first_step = game.update(&player_actions[0]).unwrap();
+        let played_game = play(game, &mut log, &player_actions);
+
+        let replayed_games: Vec<Game> = log.replay().unwrap().collect();
+
+        assert_eq!(replayed_games.len(), 3);
+        assert_eq!(SavedGame::from_game(&replayed_games[1]), SavedGame::from_game(&first_step));
+        assert_eq!(SavedGame::from_game(&replayed_games[2]), SavedGame::from_game(&played_game));
+    }
+
+    #[test]
+    fn game_at_step() {
+        let game = Game::from_board(Board::new_test(3, 3, std::collections::HashSet::from([Coordinate{ x: 0, y: 0 }])))
+            .add_player(Player::new_with_id(1, "hyeyoung"));
+        let mut log = ReplayLog::new(&game);
+        play(game, &mut log, &[PlayerAction{ player_id: 1, coordinate: Coordinate{ x: 0, y: 0 }, action: Action::Flag }]);
+
+        let before = log.game_at(0).unwrap().unwrap();
+        let after = log.game_at(1).unwrap().unwrap();
+
+        assert_eq!(before.board.get_tile(&Coordinate{ x: 0, y: 0 }), &TileStatus::Hidden);
+        assert_eq!(after.board.get_tile(&Coordinate{ x: 0, y: 0 }), &TileStatus::Flagged(1));
+        assert!(log.game_at(2).unwrap().is_none());
+    }
+
+    #[test]
+    fn replay_keeps_undo_penalties() {
+        let game = Game::from_board(Board::new_test(3, 3, std::collections::HashSet::from([Coordinate{ x: 0, y: 0 }])))
+            .add_player(Player::new_with_id(1, "hyeyoung"));
+        let mut log = ReplayLog::new(&game);
+        let player_actions = [
+            PlayerAction{ player_id: 1, coordinate: Coordinate{ x: 1, y: 1 }, action: Action::Reveal },
+            PlayerAction{ player_id: 1, coordinate: Coordinate{ x: 0, y: 0 }, action: Action::Flag },
+        ];
+
+        let history = player_actions.iter().fold(GameHistory::new(game, UndoPolicy::Penalized(5)), |history, player_action| {
+            log.record(player_action);
+            history.update(player_action).unwrap()
+        });
+        let history = history.undo(&1).unwrap().undo(&1).unwrap().redo().unwrap();
+        log.undo(&1, 5);
+        log.undo(&1, 5);
+        log.redo();
+
+        let json = serde_json::to_string(&log).unwrap();
+        let replayed_game = serde_json::from_str::<ReplayLog>(&json).unwrap().replay().unwrap().last().unwrap();
+
+        assert_eq!(replayed_game.get_player(&1).points, 1 - 10);
+        assert_eq!(SavedGame::from_game(&replayed_game), SavedGame::from_game(history.game()));
+    }
+
+    #[test]
+    fn log_round_trips_through_json() {
+        let game = Game::from_board(Board::new_test(2, 2, std::collections::HashSet::from([Coordinate{ x: 0, y: 0 }])))
+            .add_player(Player::new_with_id(1, "hyeyoung"));
+        let mut log = ReplayLog::new(&game);
+        log.record(&PlayerAction{ player_id: 1, coordinate: Coordinate{ x: 1, y: 1 }, action: Action::Reveal });
+
+        let json = serde_json::to_string(&log).unwrap();
+
+        assert_eq!(serde_json::from_str::<ReplayLog>(&json).unwrap(), log);
+    }
+}
@@ -0,0 +1,422 @@
+// Saving a game to a file and loading it back
+
+use std::collections::HashSet;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::rc::Rc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::core::board::{Board, BoardMap, Coordinate, PendingMines, Tile, TileStatus};
+use crate::core::game::{Game, GameStatus, Mode, Progress, Rules};
+use crate::core::player::{Player, PlayerId};
+use crate::core::turn::{TurnOrder, Turns};
+
+// Bump whenever the layout of SavedGame changes
+// Version 1 saves have no turn state and load with a fresh round-robin round
+// Version 2 saves have no parallel boards or progress, and load as shared board games
+pub const SAVE_VERSION: u32 = 3;
+
+// On-disk layout of a Game
+// im::HashMap and the shared mine set are stored as plain lists
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedGame {
+    pub version: u32,
+    pub h_size: u32,
+    pub v_size: u32,
+    pub seed: u64,
+    #[serde(flatten)]
+    board: SavedBoard,
+    pub players: Vec<Player>,
+    pub status: GameStatus,
+    pub rules: Rules,
+    eliminated: Vec<PlayerId>,
+    #[serde(default)]
+    turns: Option<Turns>,
+    #[serde(default)]
+    pub mode: Mode,
+    #[serde(default)]
+    boards: Vec<(PlayerId, SavedBoard)>,
+    #[serde(default)]
+    progress: Vec<(PlayerId, Progress)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct SavedBoard {
+    tiles: Vec<(Coordinate, TileStatus)>,
+    mines: Vec<Coordinate>,
+    pending_mines: Option<PendingMines>,
+    #[serde(default)]
+    needs_guess: bool,
+}
+
+#[derive(Debug)]
+pub enum LoadErr {
+    Io(io::Error),
+    Format(serde_json::Error),
+    UnsupportedVersion(u32),
+    Inconsistent(String), // the file parsed, but does not describe a possible game
+}
+
+impl From<io::Error> for LoadErr {
+    fn from(err: io::Error) -> Self {
+        LoadErr::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for LoadErr {
+    fn from(err: serde_json::Error) -> Self {
+        LoadErr::Format(err)
+    }
+}
+
+fn inconsistent(message: String) -> LoadErr {
+    LoadErr::Inconsistent(message)
+}
+
+impl SavedGame {
+    // Lists are sorted so that the same game always saves to the same file
+    pub fn from_game(game: &Game) -> SavedGame {
+        let mut players: Vec<Player> = game.players.values().cloned().collect();
+        players.sort_by_key(|player| player.id);
+
+        let mut eliminated: Vec<PlayerId> = game.eliminated.iter().copied().collect();
+        eliminated.sort();
+
+        let mut boards: Vec<(PlayerId, SavedBoard)> = game.boards.iter()
+            .map(|(player_id, board)| (*player_id, SavedBoard::from_board(board)))
+            .collect();
+        boards.sort_by_key(|(player_id, _)| *player_id);
+
+        let mut progress: Vec<(PlayerId, Progress)> = game.progress.iter().map(|(player_id, progress)| (*player_id, *progress)).collect();
+        progress.sort_by_key(|(player_id, _)| *player_id);
+
+        SavedGame {
+            version: SAVE_VERSION,
+            h_size: game.board.h_size,
+            v_size: game.board.v_size,
+            seed: game.board.seed,
+            board: SavedBoard::from_board(&game.board),
+            players,
+            status: game.status,
+            rules: game.rules,
+            eliminated,
+            turns: Some(game.turns.clone()),
+            mode: game.mode,
+            boards,
+            progress,
+        }
+    }
+
+    // Rebuilds the game after checking that the saved state could have been reached by playing
+    pub fn into_game(self) -> Result<Game, LoadErr> {
+        if !(1..=SAVE_VERSION).contains(&self.version) {
+            return Err(LoadErr::UnsupportedVersion(self.version));
+        }
+
+        if self.h_size == 0 || self.v_size == 0 {
+            return Err(inconsistent(format!("board size {}x{} is empty", self.h_size, self.v_size)));
+        }
+
+        let board = self.board.to_board(self.h_size, self.v_size, self.seed)?;
+        let players: im::HashMap<PlayerId, Player> = self.players.iter()
+            .map(|player| (player.id, player.clone()))
+            .collect();
+
+        if players.len() != self.players.len() {
+            return Err(inconsistent("player ids are not unique".to_string()));
+        }
+
+        let boards: im::HashMap<PlayerId, Board> = self.boards.iter()
+            .map(|(player_id, saved_board)| Ok((*player_id, saved_board.to_board(self.h_size, self.v_size, self.seed)?)))
+            .collect::<Result<_, LoadErr>>()?;
+
+        // every player has their own board with parallel boards, and nobody has one otherwise
+        let expected_boards = match self.mode {
+            Mode::SharedBoard => 0,
+            Mode::ParallelBoards => players.len(),
+        };
+        if boards.len() != self.boards.len() || boards.len() != expected_boards || !boards.keys().all(|id| players.contains_key(id)) {
+            return Err(inconsistent(format!("expected one board for each player with {:?}", self.mode)));
+        }
+
+        for board in boards.values().chain(std::iter::once(&board)) {
+            for (coordinate, tile_status) in board.iter() {
+                if let TileStatus::Flagged(player_id) = tile_status {
+                    if !players.contains_key(player_id) {
+                        return Err(inconsistent(format!("{:?} is flagged by unknown player {}", coordinate, player_id)));
+                    }
+                }
+            }
+        }
+
+        if let Some(player_id) = self.eliminated.iter().find(|id| !players.contains_key(id)) {
+            return Err(inconsistent(format!("eliminated player {} is not in the game", player_id)));
+        }
+
+        if let Some((player_id, _)) = self.progress.iter().find(|(id, _)| !players.contains_key(id)) {
+            return Err(inconsistent(format!("progress of player {} who is not in the game", player_id)));
+        }
+
+        if let GameStatus::Lost(player_id, coordinate) = self.status {
+            let lost_on = boards.get(&player_id).unwrap_or(&board);
+            let on_board = coordinate.x < self.h_size && coordinate.y < self.v_size;
+            if !players.contains_key(&player_id) || !on_board || lost_on.get_tile(&coordinate) != &TileStatus::Revealed(Tile::Mine) {
+                return Err(inconsistent(format!("player {} could not have lost at {:?}", player_id, coordinate)));
+            }
+        }
+
+        if let Some(turns) = &self.turns {
+            if let Some(player_id) = turns.turn_order.iter().find(|id| !players.contains_key(id)) {
+                return Err(inconsistent(format!("player {} in the turn order is not in the game", player_id)));
+            }
+            if turns.current_turn >= turns.turn_order.len().max(1) {
+                return Err(inconsistent(format!("turn {} is past the end of the round", turns.current_turn)));
+            }
+        }
+
+        let game = Game {
+            board,
+            players,
+            status: self.status,
+            rules: self.rules,
+            eliminated: self.eliminated.into_iter().collect(),
+            turns: Turns::new(TurnOrder::RoundRobin, false),
+            mode: self.mode,
+            boards,
+            progress: self.progress.into_iter().collect(),
+        };
+
+        Ok(match self.turns {
+            Some(turns) => Game { turns, ..game },
+            None => game.with_turn_order(TurnOrder::RoundRobin, false),
+        })
+    }
+}
+
+impl SavedBoard {
+    fn from_board(board: &Board) -> SavedBoard {
+        let mut tiles: Vec<(Coordinate, TileStatus)> = board.iter().map(|(c, tile_status)| (*c, tile_status.clone())).collect();
+        tiles.sort_by_key(|(c, _)| (c.y, c.x));
+
+        let mut mines: Vec<Coordinate> = board.mine_coordinates.iter().copied().collect();
+        mines.sort_by_key(|c| (c.y, c.x));
+
+        SavedBoard { tiles, mines, pending_mines: board.pending_mines, needs_guess: board.needs_guess }
+    }
+
+    fn to_board(&self, h_size: u32, v_size: u32, seed: u64) -> Result<Board, LoadErr> {
+        let num_tiles = h_size.checked_mul(v_size)
+            .ok_or_else(|| inconsistent(format!("board size {}x{} is too large", h_size, v_size)))? as usize;
+        let board_map: BoardMap = self.tiles.iter().cloned().collect();
+        let mine_coordinates: HashSet<Coordinate> = self.mines.iter().copied().collect();
+
+        let within_bounds = |c: &Coordinate| c.x < h_size && c.y < v_size;
+
+        // every coordinate present exactly once
+        if self.tiles.len() != num_tiles || board_map.len() != num_tiles || !board_map.keys().all(within_bounds) {
+            return Err(inconsistent(format!("expected one tile for each of the {} coordinates", num_tiles)));
+        }
+
+        if mine_coordinates.len() != self.mines.len() || !mine_coordinates.iter().all(within_bounds) {
+            return Err(inconsistent("mines must be unique and on the board".to_string()));
+        }
+
+        match self.pending_mines {
+            Some(pending_mines) => {
+                if !mine_coordinates.is_empty() || board_map.values().any(|t| t != &TileStatus::Hidden) {
+                    return Err(inconsistent("a board waiting for its first move cannot have mines or moves".to_string()));
+                }
+                if pending_mines.num_mines as usize >= num_tiles {
+                    return Err(inconsistent(format!("{} mines do not fit on {} tiles", pending_mines.num_mines, num_tiles)));
+                }
+            },
+            None => {
+                if mine_coordinates.len() >= num_tiles {
+                    return Err(inconsistent(format!("{} mines do not fit on {} tiles", mine_coordinates.len(), num_tiles)));
+                }
+            },
+        }
+
+        let board = Board {
+            h_size,
+            v_size,
+            seed,
+            board_map,
+            mine_coordinates: Rc::new(mine_coordinates),
+            pending_mines: self.pending_mines,
+            needs_guess: self.needs_guess,
+        };
+
+        // revealed hints must match the mines around them, and only mines can be flagged
+        for (coordinate, tile_status) in board.iter() {
+            let consistent = match tile_status {
+                TileStatus::Hidden => true,
+                TileStatus::Flagged(_) | TileStatus::Revealed(Tile::Mine) => board.is_mine(coordinate),
+                TileStatus::Revealed(Tile::Hint(n)) => !board.is_mine(coordinate) && board.get_hint(coordinate) == *n,
+            };
+
+            if !consistent {
+                return Err(inconsistent(format!("{:?} does not match the mines around it", coordinate)));
+            }
+        }
+
+        Ok(board)
+    }
+}
+
+impl Game {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(&SavedGame::from_game(self)).expect("a game should always serialize")
+    }
+
+    pub fn from_json(json: &str) -> Result<Game, LoadErr> {
+        let saved_game: SavedGame = serde_json::from_str(json)?;
+        saved_game.into_game()
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        fs::write(path, self.to_json())
+    }
+
+    pub fn load(path: &Path) -> Result<Game, LoadErr> {
+        Game::from_json(&fs::read_to_string(path)?)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::board::FirstMove;
+    use crate::core::game::Difficulty;
+    use crate::core::player::{Action, PlayerAction};
+
+    fn played_game() -> Game {
+        Game::from_board(Board::new_test(3, 3, HashSet::from([Coordinate{ x: 0, y: 0 }])))
+            .with_rules(Rules::Elimination)
+            .add_player(Player::new_with_id(1, "hyeyoung"))
+            .add_player(Player::new_with_id(2, "charlie"))
+            .update(&PlayerAction{ player_id: 1, coordinate: Coordinate{ x: 1, y: 1 }, action: Action::Reveal }).unwrap()
+            .update(&PlayerAction{ player_id: 2, coordinate: Coordinate{ x: 0, y: 0 }, action: Action::Flag }).unwrap()
+    }
+
+    #[test]
+    fn save_and_load() {
+        let game = played_game();
+        let loaded_game = Game::from_json(&game.to_json()).unwrap();
+
+        assert_eq!(SavedGame::from_game(&loaded_game), SavedGame::from_game(&game));
+        assert_eq!(loaded_game.get_player(&2).points, 2);
+        assert_eq!(loaded_game.board.get_tile(&Coordinate{ x: 0, y: 0 }), &TileStatus::Flagged(2));
+    }
+
+    #[test]
+    fn save_and_load_parallel_boards() {
+        let game = Game::from_board(Board::new_deferred(5, 5, Difficulty::Easy, 9, FirstMove::SafeOpening))
+            .with_mode(Mode::ParallelBoards)
+            .add_player(Player::new_with_id(1, "hyeyoung"))
+            .add_player(Player::new_with_id(2, "charlie"))
+            .update(&PlayerAction{ player_id: 1, coordinate: Coordinate{ x: 2, y: 2 }, action: Action::Reveal }).unwrap();
+        let loaded_game = Game::from_json(&game.to_json()).unwrap();
+
+        assert_eq!(SavedGame::from_game(&loaded_game), SavedGame::from_game(&game));
+        assert!(loaded_game.player_board(&1).mines_placed());
+        assert!(!loaded_game.player_board(&2).mines_placed());
+        assert_eq!(loaded_game.progress(&1).moves, 1);
+    }
+
+    #[test]
+    fn missing_parallel_board() {
+        let mut saved_game = SavedGame::from_game(&played_game());
+        saved_game.mode = Mode::ParallelBoards;
+
+        assert!(matches!(saved_game.into_game(), Err(LoadErr::Inconsistent(_))));
+    }
+
+    #[test]
+    fn save_and_load_before_first_move() {
+        let game = Game::from_board(Board::new_deferred(5, 5, Difficulty::Easy, 9, FirstMove::SafeOpening));
+        let loaded_game = Game::from_json(&game.to_json()).unwrap();
+
+        assert!(!loaded_game.board.mines_placed());
+        assert_eq!(loaded_game.seed(), 9);
+        assert_eq!(loaded_game.board.num_mines(), game.board.num_mines());
+    }
+
+    #[test]
+    fn unsupported_version() {
+        let mut saved_game = SavedGame::from_game(&played_game());
+        saved_game.version = SAVE_VERSION + 1;
+
+        assert!(matches!(saved_game.into_game(), Err(LoadErr::UnsupportedVersion(_))));
+    }
+
+    #[test]
+    fn version_1_starts_a_new_round() {
+        let mut saved_game = SavedGame::from_game(&played_game());
+        saved_game.version = 1;
+        saved_game.turns = None;
+
+        assert_eq!(saved_game.into_game().unwrap().current_player().unwrap().id, 1);
+    }
+
+    #[test]
+    fn turn_past_end_of_round() {
+        let mut saved_game = SavedGame::from_game(&played_game());
+        if let Some(turns) = &mut saved_game.turns {
+            turns.current_turn = 2;
+        }
+
+        assert!(matches!(saved_game.into_game(), Err(LoadErr::Inconsistent(_))));
+    }
+
+    #[test]
+    fn missing_tile() {
+        let mut saved_game = SavedGame::from_game(&played_game());
+        saved_game.board.tiles.pop();
+
+        assert!(matches!(saved_game.into_game(), Err(LoadErr::Inconsistent(_))));
+    }
+
+    #[test]
+    fn wrong_hint() {
+        let mut saved_game = SavedGame::from_game(&played_game());
+        let hint_coordinate = Coordinate{ x: 1, y: 1 };
+        saved_game.board.tiles.retain(|(c, _)| c != &hint_coordinate);
+        saved_game.board.tiles.push((hint_coordinate, TileStatus::Revealed(Tile::Hint(2))));
+
+        assert!(matches!(saved_game.into_game(), Err(LoadErr::Inconsistent(_))));
+    }
+
+    #[test]
+    fn flag_by_unknown_player() {
+        let mut saved_game = SavedGame::from_game(&played_game());
+        saved_game.players.retain(|player| player.id != 2);
+
+        assert!(matches!(saved_game.into_game(), Err(LoadErr::Inconsistent(_))));
+    }
+
+    #[test]
+    fn lost_outside_the_board() {
+        let mut saved_game = SavedGame::from_game(&played_game());
+        saved_game.status = GameStatus::Lost(1, Coordinate{ x: 3, y: 0 });
+
+        assert!(matches!(saved_game.into_game(), Err(LoadErr::Inconsistent(_))));
+    }
+
+    #[test]
+    fn board_size_overflows() {
+        let mut saved_game = SavedGame::from_game(&played_game());
+        saved_game.h_size = u32::MAX;
+        saved_game.v_size = 2;
+
+        assert!(matches!(saved_game.into_game(), Err(LoadErr::Inconsistent(_))));
+    }
+
+    #[test]
+    fn not_json() {
+        assert!(matches!(Game::from_json("minesweeper"), Err(LoadErr::Format(_))));
+    }
+}
@@ -0,0 +1,591 @@
+// Logical solver: deduces safe tiles and mines from revealed hints only
+//
+// Used to build no-guess boards; works on a PlayerView, so anything a player sees can be solved too.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use crate::core::board::{Board, Coordinate, Tile, TileStatus};
+use crate::core::debug;
+use crate::core::player::{Action, PlayerAction, PlayerId};
+use crate::core::view::PlayerView;
+
+// The solver flags tiles under this id when it plays a board by itself
+const SOLVER_ID: PlayerId = 0;
+
+// Groups of tiles next to hints are only enumerated up to this size, which keeps the search quick
+pub(crate) const MAX_ENUMERATED_TILES: usize = 24;
+
+// Which rule settled a tile, e.g. to explain a hint
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rule {
+    SingleTile,  // a hint already has all its mines, or needs every hidden tile around it
+    Subset,      // the hidden tiles around one hint include all of another's
+    Overlap,     // two hints share some hidden tiles
+    MineCount,   // the total number of mines
+    Enumeration, // every way of placing mines around the hints agrees
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct Deductions {
+    pub safe: HashSet<Coordinate>,
+    pub mines: HashSet<Coordinate>,
+    pub rules: HashMap<Coordinate, Rule>, // the rule that settled each tile
+}
+
+impl Deductions {
+    pub fn is_empty(&self) -> bool {
+        self.safe.is_empty() && self.mines.is_empty()
+    }
+
+    // Adds what `rule` found and nobody knew yet
+    fn merge(&mut self, rule: Rule, mut found: Deductions, known: &Deductions) {
+        found.safe.retain(|c| !known.safe.contains(c) && !self.safe.contains(c));
+        found.mines.retain(|c| !known.mines.contains(c) && !self.mines.contains(c));
+
+        if debug::is_enabled() {
+            if let Some(message) = debug::deductions(&format!("{:?}", rule), &found.safe, &found.mines) {
+                debug::log(|| message);
+            }
+        }
+
+        self.rules.extend(found.safe.iter().chain(&found.mines).map(|c| (*c, rule)));
+        self.safe.extend(found.safe);
+        self.mines.extend(found.mines);
+    }
+}
+
+// "exactly `mines` of `coordinates` are mines"
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Constraint {
+    pub(crate) coordinates: HashSet<Coordinate>,
+    pub(crate) mines: u32,
+}
+
+// Uses only what a player can see: hidden, flagged and revealed tiles, and the total number of mines
+// Flagged tiles count as mines because flagging a non-mine reveals it
+pub fn deduce(view: &PlayerView) -> Deductions {
+    let mut deductions = Deductions::default();
+
+    loop {
+        let found = deduce_step(view, &deductions);
+
+        if found.is_empty() {
+            return deductions;
+        }
+
+        deductions.rules.extend(found.rules);
+        deductions.safe.extend(found.safe);
+        deductions.mines.extend(found.mines);
+    }
+}
+
+// One pass of every rule over what is still unknown
+fn deduce_step(view: &PlayerView, known: &Deductions) -> Deductions {
+    let constraints = constraints(view, known);
+    let mut found = Deductions::default();
+
+    found.merge(Rule::SingleTile, single_tile(&constraints), known);
+    found.merge(Rule::Subset, subset(&constraints), known);
+    found.merge(Rule::Overlap, overlap(&constraints), known);
+    found.merge(Rule::MineCount, settled(&global_constraint(view, known)), known);
+
+    // enumeration is by far the slowest, so it only runs once nothing simpler applies
+    if found.is_empty() {
+        found.merge(Rule::Enumeration, enumeration(view, known, &constraints), known);
+    }
+
+    found
+}
+
+// What a constraint settles on its own: no mines left, or as many mines as tiles
+pub(crate) fn settled(constraint: &Constraint) -> Deductions {
+    let mut found = Deductions::default();
+
+    if constraint.coordinates.is_empty() {
+        return found;
+    }
+
+    if constraint.mines == 0 {
+        found.safe.extend(&constraint.coordinates);
+    } else if constraint.mines as usize == constraint.coordinates.len() {
+        found.mines.extend(&constraint.coordinates);
+    }
+
+    found
+}
+
+fn single_tile(constraints: &[Constraint]) -> Deductions {
+    let mut found = Deductions::default();
+
+    for constraint in constraints {
+        let settled = settled(constraint);
+        found.safe.extend(settled.safe);
+        found.mines.extend(settled.mines);
+    }
+
+    found
+}
+
+// If a is a subset of b (b is a superset of a), then b - a holds b.mines - a.mines mines
+pub(crate) fn subset(constraints: &[Constraint]) -> Deductions {
+    let mut found = Deductions::default();
+
+    for a in constraints {
+        for b in constraints {
+            if a != b && a.coordinates.is_subset(&b.coordinates) {
+                let difference = Constraint {
+                    coordinates: b.coordinates.difference(&a.coordinates).copied().collect(),
+                    mines: b.mines.saturating_sub(a.mines),
+                };
+
+                let settled = settled(&difference);
+                found.safe.extend(settled.safe);
+                found.mines.extend(settled.mines);
+            }
+        }
+    }
+
+    found
+}
+
+// When a and b only share some tiles, b bounds how many mines the shared tiles hold,
+// which can settle the tiles only a touches
+// For example, a 1 and a 2 sharing two tiles: the 2's third tile is a mine and the 1's other tiles are safe
+pub(crate) fn overlap(constraints: &[Constraint]) -> Deductions {
+    let mut found = Deductions::default();
+
+    for a in constraints {
+        for b in constraints {
+            let shared = a.coordinates.intersection(&b.coordinates).count() as u32;
+            if a == b || shared == 0 {
+                continue;
+            }
+
+            let only_a: HashSet<Coordinate> = a.coordinates.difference(&b.coordinates).copied().collect();
+            let only_b = b.coordinates.len() as u32 - shared;
+
+            // the fewest and the most mines the shared tiles can hold
+            let fewest = b.mines.saturating_sub(only_b);
+            let most = shared.min(a.mines).min(b.mines);
+
+            if fewest >= a.mines {
+                found.safe.extend(&only_a);
+            } else if a.mines - most == only_a.len() as u32 {
+                found.mines.extend(&only_a);
+            }
+        }
+    }
+
+    found
+}
+
+// Every unknown tile takes part in the total number of mines
+pub(crate) fn global_constraint(view: &PlayerView, known: &Deductions) -> Constraint {
+    let coordinates: HashSet<Coordinate> = view.iter()
+        .map(|(coordinate, _)| *coordinate)
+        .filter(|c| is_unknown(view, c, known))
+        .collect();
+    let known_mines = view.iter()
+        .filter(|(c, _)| is_known_mine(view, c, known))
+        .count() as u32;
+
+    Constraint { coordinates, mines: view.num_mines.saturating_sub(known_mines) }
+}
+
+// Tries every way of placing mines next to the hints, together with the total number of mines:
+// a tile that is a mine in none of them is safe, and one that is a mine in all of them is a mine.
+// Tiles away from the hints are settled the same way as a whole.
+fn enumeration(view: &PlayerView, known: &Deductions, constraints: &[Constraint]) -> Deductions {
+    let mut found = Deductions::default();
+    let frontier = Frontier::new(view, known, constraints);
+
+    for (i, group) in frontier.groups.iter().enumerate() {
+        let Some(placements) = &group.placements else { continue };
+        let others = frontier.mine_totals(Some(i));
+
+        for (t, tile) in group.tiles.iter().enumerate() {
+            let mut can_be_mine = false;
+            let mut can_be_safe = false;
+
+            for (num_mines, (num_placements, mine_counts)) in placements {
+                if !others.iter().any(|other_mines| frontier.fits(num_mines + other_mines)) {
+                    continue;
+                }
+
+                can_be_mine |= mine_counts[t] > 0;
+                can_be_safe |= mine_counts[t] < *num_placements;
+            }
+
+            match (can_be_mine, can_be_safe) {
+                (false, true) => { found.safe.insert(*tile); },
+                (true, false) => { found.mines.insert(*tile); },
+                _ => {},
+            }
+        }
+    }
+
+    let interior_mines: HashSet<u32> = frontier.mine_totals(None).into_iter()
+        .filter(|frontier_mines| frontier.fits(*frontier_mines))
+        .map(|frontier_mines| frontier.remaining_mines - frontier_mines)
+        .collect();
+
+    if interior_mines == HashSet::from([0]) {
+        found.safe.extend(&frontier.interior);
+    } else if interior_mines == HashSet::from([frontier.interior.len() as u32]) {
+        found.mines.extend(&frontier.interior);
+    }
+
+    found
+}
+
+// The unknown tiles next to hints, split into groups that no hint connects, and the unknown tiles away from hints
+pub(crate) struct Frontier {
+    pub(crate) groups: Vec<Group>,
+    pub(crate) interior: HashSet<Coordinate>,
+    pub(crate) remaining_mines: u32, // mines among all unknown tiles
+}
+
+pub(crate) struct Group {
+    pub(crate) tiles: Vec<Coordinate>,
+    // for each number of mines, how many placements fit the hints and how many of them put a mine on each tile;
+    // None when the group is too big to enumerate
+    pub(crate) placements: Option<BTreeMap<u32, (u64, Vec<u64>)>>,
+}
+
+impl Frontier {
+    pub(crate) fn new(view: &PlayerView, known: &Deductions, constraints: &[Constraint]) -> Frontier {
+        let global = global_constraint(view, known);
+
+        let groups: Vec<Group> = connected(constraints).into_iter()
+            .map(|group| {
+                let mut tiles: Vec<Coordinate> = group.iter().flat_map(|c| c.coordinates.iter().copied()).collect::<HashSet<Coordinate>>().into_iter().collect();
+                tiles.sort_by_key(|c| (c.y, c.x));
+                let placements = (tiles.len() <= MAX_ENUMERATED_TILES).then(|| placements(&tiles, &group));
+
+                Group { tiles, placements }
+            })
+            .collect();
+
+        let interior = global.coordinates.iter()
+            .filter(|c| !groups.iter().any(|group| group.tiles.contains(c)))
+            .copied()
+            .collect();
+
+        Frontier { groups, interior, remaining_mines: global.mines }
+    }
+
+    // Whether the rest of the mines fit on the tiles away from the hints
+    pub(crate) fn fits(&self, frontier_mines: u32) -> bool {
+        frontier_mines <= self.remaining_mines && self.remaining_mines - frontier_mines <= self.interior.len() as u32
+    }
+
+    // Every number of mines the groups can hold together, leaving out group `except`
+    // A group too big to enumerate could hold any number
+    pub(crate) fn mine_totals(&self, except: Option<usize>) -> HashSet<u32> {
+        self.groups.iter().enumerate()
+            .filter(|(i, _)| Some(*i) != except)
+            .fold(HashSet::from([0]), |totals, (_, group)| {
+                let counts: Vec<u32> = match &group.placements {
+                    Some(placements) => placements.keys().copied().collect(),
+                    None => (0..=group.tiles.len() as u32).collect(),
+                };
+
+                totals.iter().flat_map(|total| counts.iter().map(move |count| total + count)).collect()
+            })
+    }
+}
+
+// Splits constraints into groups that share no tiles with each other
+fn connected(constraints: &[Constraint]) -> Vec<Vec<&Constraint>> {
+    let mut unvisited: Vec<&Constraint> = constraints.iter().collect();
+    let mut groups = Vec::new();
+
+    while let Some(first) = unvisited.pop() {
+        let mut group = vec![first];
+        let mut tiles = first.coordinates.clone();
+
+        loop {
+            let (touching, rest): (Vec<&Constraint>, Vec<&Constraint>) = unvisited.into_iter()
+                .partition(|constraint| !constraint.coordinates.is_disjoint(&tiles));
+            unvisited = rest;
+
+            if touching.is_empty() {
+                break;
+            }
+            for constraint in touching {
+                tiles.extend(&constraint.coordinates);
+                group.push(constraint);
+            }
+        }
+
+        groups.push(group);
+    }
+
+    groups
+}
+
+// Backtracks over the tiles of a group, keeping every constraint satisfiable on the way
+fn placements(tiles: &[Coordinate], constraints: &[&Constraint]) -> BTreeMap<u32, (u64, Vec<u64>)> {
+    let index: HashMap<Coordinate, usize> = tiles.iter().enumerate().map(|(i, c)| (*c, i)).collect();
+    let mut search = Search {
+        needed: constraints.iter().map(|constraint| constraint.mines).collect(),
+        mines: vec![0; constraints.len()],
+        unassigned: constraints.iter().map(|constraint| constraint.coordinates.len() as u32).collect(),
+        tile_constraints: vec![Vec::new(); tiles.len()],
+        assignment: vec![false; tiles.len()],
+        placements: BTreeMap::new(),
+    };
+
+    for (j, constraint) in constraints.iter().enumerate() {
+        for coordinate in &constraint.coordinates {
+            search.tile_constraints[index[coordinate]].push(j);
+        }
+    }
+
+    search.run(0, 0);
+    search.placements
+}
+
+struct Search {
+    needed: Vec<u32>,                 // mines each constraint needs
+    mines: Vec<u32>,                  // mines placed so far around each constraint
+    unassigned: Vec<u32>,             // tiles around each constraint still open
+    tile_constraints: Vec<Vec<usize>>, // the constraints around each tile
+    assignment: Vec<bool>,
+    placements: BTreeMap<u32, (u64, Vec<u64>)>,
+}
+
+impl Search {
+    fn run(&mut self, tile: usize, num_mines: u32) {
+        if tile == self.assignment.len() {
+            let (num_placements, mine_counts) = self.placements.entry(num_mines)
+                .or_insert_with(|| (0, vec![0; self.assignment.len()]));
+            *num_placements += 1;
+            mine_counts.iter_mut().zip(&self.assignment).for_each(|(count, is_mine)| *count += *is_mine as u64);
+            return;
+        }
+
+        for is_mine in [false, true] {
+            if self.assign(tile, is_mine) {
+                self.run(tile + 1, num_mines + is_mine as u32);
+            }
+            self.unassign(tile, is_mine);
+        }
+    }
+
+    // Places the tile and checks every constraint around it can still be met
+    fn assign(&mut self, tile: usize, is_mine: bool) -> bool {
+        self.assignment[tile] = is_mine;
+
+        self.tile_constraints[tile].iter().fold(true, |fits, &j| {
+            self.unassigned[j] -= 1;
+            self.mines[j] += is_mine as u32;
+            fits && self.mines[j] <= self.needed[j] && self.mines[j] + self.unassigned[j] >= self.needed[j]
+        })
+    }
+
+    fn unassign(&mut self, tile: usize, is_mine: bool) {
+        for &j in &self.tile_constraints[tile] {
+            self.unassigned[j] += 1;
+            self.mines[j] -= is_mine as u32;
+        }
+    }
+}
+
+fn is_unknown(view: &PlayerView, coordinate: &Coordinate, known: &Deductions) -> bool {
+    matches!(view.get_tile(coordinate), TileStatus::Hidden)
+        && !known.safe.contains(coordinate)
+        && !known.mines.contains(coordinate)
+}
+
+fn is_known_mine(view: &PlayerView, coordinate: &Coordinate, known: &Deductions) -> bool {
+    matches!(view.get_tile(coordinate), TileStatus::Flagged(_) | TileStatus::Revealed(Tile::Mine))
+        || known.mines.contains(coordinate)
+}
+
+// One constraint per revealed hint that still touches unknown tiles
+pub(crate) fn constraints(view: &PlayerView, known: &Deductions) -> Vec<Constraint> {
+    view.iter()
+        .filter_map(|(coordinate, _)| constraint_at(view, coordinate, known))
+        .collect()
+}
+
+// The constraint the hint at `coordinate` puts on the unknown tiles around it, if any
+pub(crate) fn constraint_at(view: &PlayerView, coordinate: &Coordinate, known: &Deductions) -> Option<Constraint> {
+    let TileStatus::Revealed(Tile::Hint(hint)) = view.get_tile(coordinate) else { return None };
+    let neighbors = view.neighboring_coordinates(coordinate);
+
+    let coordinates: HashSet<Coordinate> = neighbors.iter()
+        .filter(|c| is_unknown(view, c, known))
+        .copied()
+        .collect();
+    let known_mines = neighbors.iter()
+        .filter(|c| is_known_mine(view, c, known))
+        .count() as i8;
+
+    // a negative count can only come from a flag on the wrong tile, which this game does not allow
+    let mines = (hint - known_mines).max(0) as u32;
+
+    (!coordinates.is_empty()).then_some(Constraint { coordinates, mines })
+}
+
+// Plays the board from `first_coordinate` using deductions only, and returns where it got stuck
+pub fn solve_from(board: &Board, first_coordinate: &Coordinate) -> Board {
+    let mut board = board.update(&PlayerAction{ player_id: SOLVER_ID, coordinate: *first_coordinate, action: Action::Reveal });
+
+    loop {
+        let deductions = deduce(&board.view());
+
+        if deductions.is_empty() {
+            return board;
+        }
+
+        board = deductions.safe.iter().fold(board, |board, coordinate| {
+            board.update(&PlayerAction{ player_id: SOLVER_ID, coordinate: *coordinate, action: Action::Reveal })
+        });
+        board = deductions.mines.iter().fold(board, |board, coordinate| {
+            board.update(&PlayerAction{ player_id: SOLVER_ID, coordinate: *coordinate, action: Action::Flag })
+        });
+    }
+}
+
+// A board is solvable when every safe tile can be revealed without guessing
+pub fn is_solvable(board: &Board, first_coordinate: &Coordinate) -> bool {
+    let solved_board = solve_from(board, first_coordinate);
+
+    let all_safe_revealed = solved_board.iter()
+        .all(|(coordinate, tile_status)| board.is_mine(coordinate) || matches!(tile_status, TileStatus::Revealed(_)));
+
+    all_safe_revealed
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reveal(board: &Board, x: u32, y: u32) -> Board {
+        board.update(&PlayerAction{ player_id: 1, coordinate: Coordinate{ x, y }, action: Action::Reveal })
+    }
+
+    #[test]
+    fn single_tile_rule() {
+        // * 1 0
+        // 1 1 0
+        // 0 0 0
+        let board = Board::new_test(3, 3, HashSet::from([Coordinate{ x: 0, y: 0 }]));
+        let board = reveal(&board, 2, 2);
+
+        let deductions = deduce(&board.view());
+
+        assert_eq!(deductions.mines, HashSet::from([Coordinate{ x: 0, y: 0 }]));
+        assert!(deductions.safe.is_empty());
+    }
+
+    #[test]
+    fn subset_rule() {
+        // 1 2 1 <- row 0 revealed
+        // ? ? ?    mines at (0,1) and (2,1)
+        let board = Board::new_test(3, 2, HashSet::from([Coordinate{ x: 0, y: 1 }, Coordinate{ x: 2, y: 1 }]));
+        let board = reveal(&reveal(&reveal(&board, 0, 0), 1, 0), 2, 0);
+
+        let deductions = deduce(&board.view());
+
+        assert_eq!(deductions.mines, HashSet::from([Coordinate{ x: 0, y: 1 }, Coordinate{ x: 2, y: 1 }]));
+        assert_eq!(deductions.safe, HashSet::from([Coordinate{ x: 1, y: 1 }]));
+    }
+
+    #[test]
+    fn global_rule() {
+        // every mine is flagged, so the remaining hidden tile is safe
+        let board = Board::new_test(2, 1, HashSet::from([Coordinate{ x: 0, y: 0 }]));
+        let board = board.update(&PlayerAction{ player_id: 1, coordinate: Coordinate{ x: 0, y: 0 }, action: Action::Flag });
+
+        let deductions = deduce(&board.view());
+
+        assert_eq!(deductions.safe, HashSet::from([Coordinate{ x: 1, y: 0 }]));
+    }
+
+    #[test]
+    fn no_deduction_needs_guess() {
+        // 1 1 <- row 0 revealed
+        // ? ?    one mine, either tile fits
+        let board = Board::new_test(2, 2, HashSet::from([Coordinate{ x: 0, y: 1 }]));
+        let board = reveal(&reveal(&board, 0, 0), 1, 0);
+
+        assert!(deduce(&board.view()).is_empty());
+        assert!(!is_solvable(&Board::new_test(2, 2, HashSet::from([Coordinate{ x: 0, y: 1 }])), &Coordinate{ x: 0, y: 0 }));
+    }
+
+    #[test]
+    fn solvable_board() {
+        let board = Board::new_test(3, 3, HashSet::from([Coordinate{ x: 0, y: 0 }]));
+
+        assert!(is_solvable(&board, &Coordinate{ x: 2, y: 2 }));
+    }
+
+    #[test]
+    fn overlap_rule() {
+        // ? 2 2 ?    mines at (1,1), (2,1) and (3,2)
+        // 1 ? ? ?    the 1 and the top left 2 share (0,0) and (1,1): the 2 puts a mine there,
+        // ? ? 3 ?    so the 1's other tiles are safe and the 2's last tile is a mine
+        let board = Board::new_test(4, 3, HashSet::from([Coordinate{ x: 1, y: 1 }, Coordinate{ x: 2, y: 1 }, Coordinate{ x: 3, y: 2 }]));
+        let board = reveal(&reveal(&reveal(&reveal(&board, 1, 0), 2, 0), 0, 1), 2, 2);
+
+        let deductions = deduce(&board.view());
+
+        assert!(deductions.mines.contains(&Coordinate{ x: 2, y: 1 }));
+        assert!(deductions.safe.contains(&Coordinate{ x: 0, y: 2 }));
+        assert!(deductions.safe.contains(&Coordinate{ x: 1, y: 2 }));
+        assert_eq!(deductions.rules[&Coordinate{ x: 0, y: 2 }], Rule::Overlap);
+    }
+
+    #[test]
+    fn enumeration_with_mine_count() {
+        // ? 2 ? ?    mines at (2,0), (0,1) and (3,1)
+        // ? ? ? ?    a mine at (0,2) would leave (0,1), (1,1) and (2,1) safe, so the 2 would need (0,0) and (2,0)
+        // ? 1 1 ?    and the right 1 one more: four mines, but there are only three
+        let board = Board::new_test(4, 3, HashSet::from([Coordinate{ x: 2, y: 0 }, Coordinate{ x: 0, y: 1 }, Coordinate{ x: 3, y: 1 }]));
+        let board = reveal(&reveal(&reveal(&board, 1, 0), 1, 2), 2, 2);
+
+        let deductions = deduce(&board.view());
+
+        assert!(deductions.safe.contains(&Coordinate{ x: 0, y: 2 }));
+        assert_eq!(deductions.rules[&Coordinate{ x: 0, y: 2 }], Rule::Enumeration);
+    }
+
+    #[test]
+    fn enumeration_away_from_hints() {
+        // ? 2 ? ?    mines at (0,1), (2,1) and (0,2)
+        // ? ? ? ?    the 3 takes all three mines, so every tile it does not touch is safe
+        // ? 3 ? ?
+        let board = Board::new_test(4, 3, HashSet::from([Coordinate{ x: 0, y: 1 }, Coordinate{ x: 2, y: 1 }, Coordinate{ x: 0, y: 2 }]));
+        let board = reveal(&reveal(&board, 1, 0), 1, 2);
+
+        let deductions = deduce(&board.view());
+
+        for (x, y) in [(0, 0), (2, 0), (3, 0), (3, 1), (3, 2)] {
+            assert!(deductions.safe.contains(&Coordinate{ x, y }), "({},{}) should be safe", x, y);
+        }
+    }
+
+    #[test]
+    fn deductions_are_never_wrong() {
+        use crate::core::game::Difficulty;
+
+        for seed in 0..500 {
+            let board = Board::new(5, 4, Difficulty::Custom { mines: 4 }, seed);
+
+            // reveal a different handful of safe tiles for every seed
+            let revealed = board.iter()
+                .map(|(coordinate, _)| *coordinate)
+                .filter(|coordinate| !board.is_mine(coordinate) && (seed >> ((coordinate.x + coordinate.y * 5) % 11)) & 1 == 1)
+                .fold(board.clone(), |board, coordinate| reveal(&board, coordinate.x, coordinate.y));
+
+            let deductions = deduce(&revealed.view());
+
+            assert!(deductions.safe.iter().all(|coordinate| !board.is_mine(coordinate)), "seed {}", seed);
+            assert!(deductions.mines.iter().all(|coordinate| board.is_mine(coordinate)), "seed {}", seed);
+        }
+    }
+}
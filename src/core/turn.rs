@@ -0,0 +1,134 @@
+// Whose turn it is in a multiplayer game
+
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use serde::{Deserialize, Serialize};
+
+use crate::core::player::PlayerId;
+
+#[derive(PartialEq, Debug, Clone, Copy, Serialize, Deserialize)]
+pub enum TurnOrder {
+    RoundRobin,     // every round goes through the players by id
+    ShuffledRounds, // every round is a new random order, drawn from the game's seed
+    Simultaneous,   // a race: everyone moves whenever they like and moves apply in arrival order
+}
+
+#[derive(PartialEq, Debug, Clone, Serialize, Deserialize)]
+pub struct Turns {
+    pub order: TurnOrder,
+    pub extra_turn_on_flag: bool, // a player who flags a mine plays again
+    pub round: u64,
+    pub turn_order: Vec<PlayerId>, // the players of this round, in the order they play
+    pub current_turn: usize,       // index into turn_order
+}
+
+impl Turns {
+    pub fn new(order: TurnOrder, extra_turn_on_flag: bool) -> Turns {
+        Turns { order, extra_turn_on_flag, round: 0, turn_order: Vec::new(), current_turn: 0 }
+    }
+
+    // Nobody is in turn in a race
+    pub fn current_player(&self) -> Option<PlayerId> {
+        match self.order {
+            TurnOrder::Simultaneous => None,
+            _ => self.turn_order.get(self.current_turn).copied(),
+        }
+    }
+
+    // Orders `players` for `round`; the same seed always gives the same rounds
+    pub fn start_round(&self, round: u64, players: &[PlayerId], seed: u64) -> Turns {
+        let mut turn_order = players.to_vec();
+        turn_order.sort();
+
+        if self.order == TurnOrder::ShuffledRounds {
+            let mut rng = rand::rngs::StdRng::seed_from_u64(seed.wrapping_add(round));
+            turn_order.shuffle(&mut rng);
+        }
+
+        Turns { round, turn_order, current_turn: 0, ..self.clone() }
+    }
+
+    // Moves on to the next player still in `players`, starting a new round after the last one
+    pub fn next(&self, extra_turn: bool, players: &[PlayerId], seed: u64) -> Turns {
+        if self.order == TurnOrder::Simultaneous || (extra_turn && self.extra_turn_on_flag) {
+            return self.clone();
+        }
+
+        let next_turn = (self.current_turn + 1..self.turn_order.len())
+            .find(|&turn| players.contains(&self.turn_order[turn]));
+
+        match next_turn {
+            Some(current_turn) => Turns { current_turn, ..self.clone() },
+            None => self.start_round(self.round + 1, players, seed),
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_robin() {
+        let players = [3, 1, 2];
+        let turns = Turns::new(TurnOrder::RoundRobin, false).start_round(0, &players, 0);
+
+        let order: Vec<PlayerId> = (0..4)
+            .scan(turns, |turns, _| {
+                let player_id = turns.current_player();
+                *turns = turns.next(false, &players, 0);
+                player_id
+            })
+            .collect();
+
+        assert_eq!(order, vec![1, 2, 3, 1]);
+    }
+
+    #[test]
+    fn shuffled_rounds_are_reproducible() {
+        let players = [1, 2, 3, 4, 5];
+        let turns = Turns::new(TurnOrder::ShuffledRounds, false);
+
+        let round = turns.start_round(1, &players, 42);
+
+        assert_eq!(round, turns.start_round(1, &players, 42));
+        let mut sorted_order = round.turn_order.clone();
+        sorted_order.sort();
+        assert_eq!(sorted_order, players.to_vec());
+    }
+
+    #[test]
+    fn extra_turn_on_flag() {
+        let players = [1, 2];
+        let turns = Turns::new(TurnOrder::RoundRobin, true).start_round(0, &players, 0);
+
+        assert_eq!(turns.next(true, &players, 0).current_player(), Some(1));
+        assert_eq!(turns.next(false, &players, 0).current_player(), Some(2));
+    }
+
+    #[test]
+    fn no_extra_turn_without_rule() {
+        let players = [1, 2];
+        let turns = Turns::new(TurnOrder::RoundRobin, false).start_round(0, &players, 0);
+
+        assert_eq!(turns.next(true, &players, 0).current_player(), Some(2));
+    }
+
+    #[test]
+    fn nobody_in_turn_in_a_race() {
+        let players = [1, 2];
+        let turns = Turns::new(TurnOrder::Simultaneous, false).start_round(0, &players, 0);
+
+        assert_eq!(turns.current_player(), None);
+        assert_eq!(turns.next(false, &players, 0).current_player(), None);
+    }
+
+    #[test]
+    fn skips_players_who_left() {
+        let turns = Turns::new(TurnOrder::RoundRobin, false).start_round(0, &[1, 2, 3], 0);
+
+        // player 2 was eliminated during player 1's turn
+        assert_eq!(turns.next(false, &[1, 3], 0).current_player(), Some(3));
+    }
+}
@@ -2,8 +2,9 @@
 use crate::single_player::text_ui::BoardSize;
 
 use crate::core::player::{Player, PlayerAction, Action};
-use crate::core::board::{Board, Coordinate, TileStatus};
-use crate::core::game::{Game};
+use crate::core::board::{Coordinate, Tile, TileStatus};
+use crate::core::view::PlayerView;
+use crate::core::game::*;
 
 #[derive(Debug)]
 pub enum CoordinateErr {
@@ -16,37 +17,65 @@ pub enum CoordinateErr {
 pub enum InvalidErr {
     InvalidAction,
     InvalidPlayer,
+    GameOver,
+    NotYourTurn,
+    TileTaken, // in a race, another player's move reached the tile first
+    NoMinesYet, // the mines of a deferred board are only placed on the first reveal
     InvalidCoordinate(CoordinateErr),
     InvalidSize,
+    TooManyMines, // no safe tile would be left
+    UndoDisabled,
+    NothingToUndo,
+    NothingToRedo,
 }
 
 pub const BOARD_MAX_SIZE: u32 = 30; // for single_player mode
 
 pub fn validate_board_size(h_size: u32, v_size: u32) -> Result<BoardSize, InvalidErr> {
-    if h_size > BOARD_MAX_SIZE && v_size > BOARD_MAX_SIZE {
+    if h_size > BOARD_MAX_SIZE || v_size > BOARD_MAX_SIZE {
         Err(InvalidErr::InvalidSize)
     } else {
-        Ok((h_size as u32, v_size as u32))
+        Ok((h_size, v_size))
     }
 }
 
+// Shares of the tiles are capped by Board::mine_count, but an exact number of mines has to fit
+pub fn validate_mine_count(h_size: u32, v_size: u32, difficulty: Difficulty) -> Result<Difficulty, InvalidErr> {
+    let num_tiles = h_size * v_size;
+
+    let fits = match difficulty {
+        Difficulty::Easy | Difficulty::Medium | Difficulty::Hard => true,
+        Difficulty::Beginner => BEGINNER_MINES < num_tiles,
+        Difficulty::Intermediate => INTERMEDIATE_MINES < num_tiles,
+        Difficulty::Expert => EXPERT_MINES < num_tiles,
+        Difficulty::Custom { mines } => mines < num_tiles,
+        Difficulty::Density(density) => (0.0..1.0).contains(&density),
+    };
+
+    if fits { Ok(difficulty) } else { Err(InvalidErr::TooManyMines) }
+}
+
 // This function validates player's chosen action for the tile at the coordinate
-pub fn validate_action(game: &Game, player_action: PlayerAction, coordinate: &Coordinate) -> Result<PlayerAction, InvalidErr> {
-    let tile_status = game.board.get_tile(coordinate);
+pub fn validate_action(view: &PlayerView, player_action: PlayerAction, coordinate: &Coordinate) -> Result<PlayerAction, InvalidErr> {
+    let tile_status = view.get_tile(coordinate);
     let action = player_action.action;
 
     match (tile_status, action) {
-        (TileStatus::Hidden, Action::Flag | Action::Reveal) => Ok(player_action),
+        (TileStatus::Hidden, Action::Flag | Action::Reveal | Action::Hint) => Ok(player_action),
+        (TileStatus::Flagged(flagged_by), Action::Unflag) if flagged_by == &player_action.player_id => Ok(player_action),
+        (TileStatus::Revealed(Tile::Hint(_)), Action::Chord) if view.can_chord(coordinate) => Ok(player_action),
          _ => Err(InvalidErr::InvalidAction),
     }
 }
 
 // This function validates player's chosen coordinate 
-pub fn validate_coordinate(board: &Board, coordinate: &Coordinate, player: &Player) -> Result<Coordinate, InvalidErr> {        
-    if board.within_bounds(&(coordinate.x as i32, coordinate.y as i32)) {
-        let tile_status = board.get_tile(coordinate);
+pub fn validate_coordinate(view: &PlayerView, coordinate: &Coordinate, player: &Player) -> Result<Coordinate, InvalidErr> {        
+    if view.within_bounds(&(coordinate.x as i32, coordinate.y as i32)) {
+        let tile_status = view.get_tile(coordinate);
 
         match tile_status {
+            // a revealed hint can still be chorded
+            TileStatus::Revealed(_) if view.can_chord(coordinate) => Ok(*coordinate),
             TileStatus::Revealed(_) => Err(InvalidErr::InvalidCoordinate(CoordinateErr::TileRevealed)),
             TileStatus::Flagged(flagged_by) => if flagged_by == &player.id { 
                 Ok(*coordinate) 
@@ -58,4 +87,98 @@ pub fn validate_coordinate(board: &Board, coordinate: &Coordinate, player: &Play
     } else {
         Err(InvalidErr::InvalidCoordinate(CoordinateErr::OutOfBounds))
     }
-}
\ No newline at end of file
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+    use crate::core::board::Board;
+    use crate::core::game::Game;
+
+    fn flagged_game() -> Game {
+        let mine_coordinate = Coordinate{ x: 0, y: 0 };
+
+        Game::from_board(Board::new_test(2, 2, HashSet::from([mine_coordinate])))
+            .add_player(Player::new_with_id(1, "hyeyoung"))
+            .add_player(Player::new_with_id(2, "charlie"))
+            .update(&PlayerAction{ player_id: 1, coordinate: mine_coordinate, action: Action::Flag })
+            .unwrap()
+    }
+
+    #[test]
+    fn either_side_too_big() {
+        assert!(validate_board_size(BOARD_MAX_SIZE, BOARD_MAX_SIZE).is_ok());
+        assert!(validate_board_size(BOARD_MAX_SIZE + 1, 1).is_err());
+        assert!(validate_board_size(1, BOARD_MAX_SIZE + 1).is_err());
+    }
+
+    #[test]
+    fn mines_leave_a_safe_tile() {
+        assert!(validate_mine_count(3, 3, Difficulty::Custom { mines: 8 }).is_ok());
+        assert!(validate_mine_count(3, 3, Difficulty::Custom { mines: 9 }).is_err());
+        assert!(validate_mine_count(9, 9, Difficulty::Expert).is_err());
+        assert!(validate_mine_count(30, 16, Difficulty::Expert).is_ok());
+        assert!(validate_mine_count(1, 1, Difficulty::Hard).is_ok());
+        assert!(validate_mine_count(9, 9, Difficulty::Density(1.0)).is_err());
+        assert!(validate_mine_count(9, 9, Difficulty::Density(-0.1)).is_err());
+    }
+
+    #[test]
+    fn unflag_own_flag() {
+        let game = flagged_game();
+        let coordinate = Coordinate{ x: 0, y: 0 };
+        let player_action = PlayerAction{ player_id: 1, coordinate, action: Action::Unflag };
+
+        assert!(validate_coordinate(&game.view(), &coordinate, game.get_player(&1)).is_ok());
+        assert!(validate_action(&game.view(), player_action, &coordinate).is_ok());
+    }
+
+    #[test]
+    fn unflag_other_players_flag() {
+        let game = flagged_game();
+        let coordinate = Coordinate{ x: 0, y: 0 };
+
+        assert!(validate_coordinate(&game.view(), &coordinate, game.get_player(&2)).is_err());
+    }
+
+    #[test]
+    fn unflag_hidden_tile() {
+        let game = flagged_game();
+        let coordinate = Coordinate{ x: 1, y: 1 };
+        let player_action = PlayerAction{ player_id: 1, coordinate, action: Action::Unflag };
+
+        assert!(validate_action(&game.view(), player_action, &coordinate).is_err());
+    }
+
+    #[test]
+    fn chord_revealed_hint() {
+        let coordinate = Coordinate{ x: 1, y: 0 };
+        let game = flagged_game()
+            .update(&PlayerAction{ player_id: 2, coordinate, action: Action::Reveal })
+            .unwrap();
+        let player_action = PlayerAction{ player_id: 1, coordinate, action: Action::Chord };
+
+        assert!(validate_coordinate(&game.view(), &coordinate, game.get_player(&1)).is_ok());
+        assert!(validate_action(&game.view(), player_action, &coordinate).is_ok());
+    }
+
+    #[test]
+    fn chord_hidden_tile() {
+        let game = flagged_game();
+        let coordinate = Coordinate{ x: 1, y: 1 };
+        let player_action = PlayerAction{ player_id: 1, coordinate, action: Action::Chord };
+
+        assert!(validate_action(&game.view(), player_action, &coordinate).is_err());
+    }
+
+    #[test]
+    fn reveal_own_flag() {
+        let game = flagged_game();
+        let coordinate = Coordinate{ x: 0, y: 0 };
+        let player_action = PlayerAction{ player_id: 1, coordinate, action: Action::Reveal };
+
+        assert!(validate_action(&game.view(), player_action, &coordinate).is_err());
+    }
+}
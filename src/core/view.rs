@@ -0,0 +1,114 @@
+// PlayerView: the board as players see it
+//
+// Anything that holds a Board can ask where the mines are. UIs, AI players and the network layer
+// get a PlayerView instead, which only knows hidden, flagged and revealed tiles.
+
+use std::collections::HashMap;
+
+use crate::core::board::{self, BoardMap, Coordinate, Tile, TileStatus};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlayerView {
+    pub h_size: u32,
+    pub v_size: u32,
+    pub num_mines: u32, // total, as announced at the start of the game
+    board_map: BoardMap,
+}
+
+impl PlayerView {
+    pub fn new(h_size: u32, v_size: u32, num_mines: u32, board_map: BoardMap) -> PlayerView {
+        PlayerView { h_size, v_size, num_mines, board_map }
+    }
+
+    pub fn get_tile(&self, coordinate: &Coordinate) -> &TileStatus {
+        self.board_map.get(coordinate).unwrap_or_else(|| panic!("tile should be at {:?}", coordinate))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&Coordinate, &TileStatus)> {
+        self.board_map.iter()
+    }
+
+    pub fn within_bounds(&self, potential_coordinate: &(i32, i32)) -> bool {
+        potential_coordinate.0 >= 0 && potential_coordinate.0 < self.h_size as i32 && potential_coordinate.1 >= 0 && potential_coordinate.1 < self.v_size as i32
+    }
+
+    pub fn neighboring_coordinates(&self, coordinate: &Coordinate) -> Vec<Coordinate> {
+        board::neighboring_coordinates(self.h_size, self.v_size, coordinate)
+    }
+
+    // Flags and revealed mines
+    pub fn num_known_mines(&self) -> u32 {
+        self.board_map.values()
+            .filter(|tile_status| matches!(tile_status, TileStatus::Flagged(_) | TileStatus::Revealed(Tile::Mine)))
+            .count() as u32
+    }
+
+    // A hint tile can be chorded when its known mines match its number and it still has hidden neighbors
+    // Revealed mines count as known mines, like flags
+    pub fn can_chord(&self, coordinate: &Coordinate) -> bool {
+        match self.get_tile(coordinate) {
+            TileStatus::Revealed(Tile::Hint(n)) => {
+                let neighbors = self.neighboring_coordinates(coordinate);
+                let known_mines = neighbors.iter()
+                    .filter(|c| matches!(self.get_tile(c), TileStatus::Flagged(_) | TileStatus::Revealed(Tile::Mine)))
+                    .count() as i8;
+                let has_hidden_neighbor = neighbors.iter()
+                    .any(|c| matches!(self.get_tile(c), TileStatus::Hidden));
+
+                known_mines == *n && has_hidden_neighbor
+            },
+            _ => false
+        }
+    }
+
+    pub fn print(&self) {
+        self.print_overlay(&HashMap::new());
+    }
+
+    // Hidden tiles show the chance that they are mines, e.g. ?25%
+    pub fn print_overlay(&self, probabilities: &HashMap<Coordinate, f64>) {
+        for y in 0..self.v_size {
+            for x in 0..self.h_size {
+                let coordinate = Coordinate{ x, y };
+                match self.get_tile(&coordinate) {
+                    TileStatus::Hidden => match probabilities.get(&coordinate) {
+                        Some(p) => print!("?{:<6}", format!("{:.0}%", p * 100.0)),
+                        None => print!("?      "),
+                    },
+                    TileStatus::Flagged(player_id) => print!("!,by {} ", player_id),
+                    TileStatus::Revealed(Tile::Hint(n)) => print!("{n}      "),
+                    TileStatus::Revealed(Tile::Mine) => print!("*      ")
+                }
+            }
+            println!();
+        }
+        println!();
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+    use crate::core::board::Board;
+    use crate::core::player::{Action, PlayerAction};
+
+    #[test]
+    fn view_matches_board() {
+        let board = Board::new_test(3, 3, HashSet::from([Coordinate{ x: 0, y: 0 }]))
+            .update(&PlayerAction{ player_id: 1, coordinate: Coordinate{ x: 2, y: 2 }, action: Action::Reveal });
+        let view = board.view();
+
+        assert_eq!(view.num_mines, 1);
+        assert!(board.iter().all(|(coordinate, tile_status)| view.get_tile(coordinate) == tile_status));
+    }
+
+    #[test]
+    fn view_counts_flags_as_known_mines() {
+        let board = Board::new_test(2, 2, HashSet::from([Coordinate{ x: 0, y: 0 }]))
+            .update(&PlayerAction{ player_id: 1, coordinate: Coordinate{ x: 0, y: 0 }, action: Action::Flag });
+
+        assert_eq!(board.view().num_known_mines(), 1);
+    }
+}
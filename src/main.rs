@@ -1,10 +1,101 @@
+use std::env;
 use std::io;
+use std::path::Path;
 
+use minesweeper_rust::core::controller::Bots;
+use minesweeper_rust::core::debug;
 use minesweeper_rust::single_player::simulation::*;
+use minesweeper_rust::multiplayer::simulation::*;
+use minesweeper_rust::multiplayer::client::join_game;
+use minesweeper_rust::multiplayer::server::host_game;
+use minesweeper_rust::single_player::cli::{arg_value, Launch, Setup, DEFAULT_ADDR};
+use minesweeper_rust::single_player::text_ui::{get_replay_file, get_name, get_num_players, load_game, new_game, show_replay, start_game};
 
+// Usage: minesweeper_rust [--mode single|multi|server|client|replay] [--players <n>] [--name <name>] [--bots <list>]
+//                         [--width <n>] [--height <n>] [--difficulty <level> | --mines <n>]
+//                         [--board Classic|SafeTile|NoGuess] [--rules Classic|Elimination|PointsOnly] [--boards Shared|Parallel]
+//                         [--turn-order RoundRobin|Shuffled|Race] [--extra-turn yes|no] [--undo-penalty <n>] [--seed <n>]
+//                         [--addr <host:port>] [--record <file>] [--debug <file>] [--training]
+//        minesweeper_rust --load <file> [--record <file>] [--training] [--undo-penalty <n>]
+//        minesweeper_rust --replay <file>
+// Anything not given is asked for
 fn main() -> io::Result<()> {
-    // let multiplayer_game = simulate_multiplayer()?;
-    let game = simulate_single_player();
+    let args: Vec<String> = env::args().skip(1).collect();
 
-    Ok(())    
+    // input piped in from a script may run out before the game is over
+    match run(&args) {
+        Err(io_err) if io_err.kind() == io::ErrorKind::UnexpectedEof => Ok(()),
+        result => result,
+    }
+}
+
+fn run(args: &[String]) -> io::Result<()> {
+    let setup = match Setup::from_args(args) {
+        Ok(setup) => setup,
+        Err(message) => {
+            eprintln!("{}", message);
+            return Ok(());
+        }
+    };
+
+    // where the mines are, what the solver deduces and every move, kept out of the console
+    if let Some(path) = arg_value(args, "--debug") {
+        debug::enable(Path::new(path))?;
+    }
+
+    let record = arg_value(args, "--record").map(Path::new);
+    let addr = arg_value(args, "--addr").map(String::as_str).unwrap_or(DEFAULT_ADDR);
+
+    if let Some(path) = arg_value(args, "--load") {
+        let game = match load_game(Path::new(path)) {
+            Ok(game) => game,
+            Err(load_err) => {
+                eprintln!("{}", load_err);
+                return Ok(());
+            }
+        };
+
+        // saves keep no bots, so everyone in a loaded game plays at this keyboard
+        let _game = if game.players.len() > 1 { resume_multiplayer(game, &Bots::new(), setup.multiplayer_undo(), record)? } else { resume_single_player(game, record, setup.training)? };
+        return Ok(());
+    }
+
+    match setup.launch {
+        Some(Launch::Replay) => {
+            let path = match arg_value(args, "--replay") {
+                Some(path) => path.clone(),
+                None => get_replay_file()?,
+            };
+            if let Err(load_err) = show_replay(Path::new(&path)) {
+                eprintln!("{}", load_err);
+            }
+        },
+        Some(Launch::Server) => {
+            let num_players = match setup.num_players {
+                Some(num_players) => num_players,
+                None => get_num_players()?,
+            };
+            host_game(addr, new_game(&setup, num_players)?, num_players as usize)?;
+        },
+        Some(Launch::Client) => {
+            let name = match &setup.name {
+                Some(name) => name.clone(),
+                None => get_name()?,
+            };
+            join_game(addr, &name)?;
+        },
+        Some(Launch::Single) => {
+            simulate_single_player(&setup, record)?;
+        },
+        Some(Launch::Multi) => {
+            simulate_multiplayer(&setup, record)?;
+        },
+        // several players take turns at this keyboard
+        None => {
+            let (game, bots) = start_game(&setup)?;
+            let _game = if game.players.len() > 1 { resume_multiplayer(game, &bots, setup.multiplayer_undo(), record)? } else { resume_single_player(game, record, setup.training)? };
+        },
+    }
+
+    Ok(())
 }
@@ -0,0 +1,201 @@
+// Bot-vs-bot games without a console, to compare strategies and scoring rules
+//
+// Every game gets its own seed, so a run can be repeated exactly (apart from the timings).
+// Bots change seats from game to game, so nobody always moves first.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use crate::core::controller::{controllers, Bots, Choice, Strategy};
+use crate::core::game::{Game, GameStatus};
+use crate::core::player::{Player, PlayerId};
+use crate::multiplayer::simulation::at_keyboard;
+
+// How one strategy did over a run
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Tally {
+    pub games: u32,
+    pub wins: u32,      // a shared win counts for everyone in it
+    pub points: i64,
+    pub moves: u64,
+    pub thinking: Duration, // time spent choosing moves
+}
+
+impl Tally {
+    pub fn win_rate(&self) -> f64 {
+        self.wins as f64 / self.games.max(1) as f64
+    }
+
+    pub fn average_points(&self) -> f64 {
+        self.points as f64 / self.games.max(1) as f64
+    }
+
+    pub fn average_moves(&self) -> f64 {
+        self.moves as f64 / self.games.max(1) as f64
+    }
+
+    pub fn time_per_move(&self) -> Duration {
+        // more moves than a u32 holds are possible over a long run
+        Duration::from_secs_f64(self.thinking.as_secs_f64() / self.moves.max(1) as f64)
+    }
+}
+
+// Plays `num_games` games between `strategies`, one seat each
+// `new_game` builds the game for a seed, before anyone joins
+pub fn run(new_game: impl Fn(u64) -> Game, strategies: &[Strategy], num_games: u32, first_seed: u64) -> Vec<(Strategy, Tally)> {
+    let mut tallies: Vec<(Strategy, Tally)> = Vec::new();
+    for strategy in strategies {
+        if !tallies.iter().any(|(s, _)| s == strategy) {
+            tallies.push((*strategy, Tally::default()));
+        }
+    }
+
+    for i in 0..num_games {
+        // seats turn by id, so joining in a different order moves everyone round the table
+        let mut game = new_game(first_seed.wrapping_add(i as u64));
+        let mut bots = Bots::new();
+        for seat in 0..strategies.len() {
+            let strategy = strategies[(seat + i as usize) % strategies.len()];
+            let bot = Player::new(format!("{:?} bot {}", strategy, seat + 1));
+            bots.insert(bot.id, strategy);
+            game = game.add_player(bot);
+        }
+
+        let (game, thinking) = play_bots(game, &bots);
+        // as announced at the end of a game: a lost game has no winners
+        let winners: Vec<PlayerId> = match game.status {
+            GameStatus::Won => game.get_winners().iter().map(|player| player.id).collect(),
+            _ => Vec::new(),
+        };
+
+        for player in game.players.values() {
+            let strategy = bots[&player.id];
+            let (_, tally) = tallies.iter_mut().find(|(s, _)| *s == strategy).expect("every strategy has a tally");
+
+            tally.games += 1;
+            tally.wins += winners.contains(&player.id) as u32;
+            tally.points += player.points as i64;
+            tally.moves += game.progress(&player.id).moves as u64;
+            tally.thinking += thinking[&player.id];
+        }
+    }
+
+    tallies
+}
+
+// Plays a game of `bots` to the end, timing how long each one thinks
+pub fn play_bots(mut game: Game, bots: &Bots) -> (Game, HashMap<PlayerId, Duration>) {
+    let mut controllers = controllers(&game, bots, || panic!("only bots play here"));
+    let mut thinking: HashMap<PlayerId, Duration> = game.players.keys().map(|id| (*id, Duration::ZERO)).collect();
+    let mut num_moves = 0;
+
+    while game.status == GameStatus::Continue {
+        let player = game.current_player().unwrap_or_else(|| at_keyboard(&game, num_moves));
+        let player_id = player.id;
+
+        let started = Instant::now();
+        let choice = controllers.get_mut(&player_id).expect("every player has a controller")
+            .choose(&game.player_view(&player_id), player)
+            .expect("bots do not read input");
+        *thinking.get_mut(&player_id).expect("every player has a timer") += started.elapsed();
+
+        let Choice::Play(player_action) = choice else { panic!("bots only play moves") };
+        game = game.update(&player_action).expect("bots only make valid moves");
+        num_moves += 1;
+    }
+
+    (game, thinking)
+}
+
+// Best win rate first, e.g.
+//   Strategy      Games  Win rate  Avg score  Avg moves  Time/move
+//   Probability    1000     61.2%       18.4       31.0     83.1µs
+pub fn table(tallies: &[(Strategy, Tally)]) -> String {
+    let mut tallies = tallies.to_vec();
+    tallies.sort_by(|(_, a), (_, b)| b.win_rate().total_cmp(&a.win_rate()));
+
+    let mut lines = vec![format!("{:<12} {:>6} {:>9} {:>10} {:>10} {:>10}", "Strategy", "Games", "Win rate", "Avg score", "Avg moves", "Time/move")];
+    lines.extend(tallies.iter().map(|(strategy, tally)| {
+        format!("{:<12} {:>6} {:>8.1}% {:>10.1} {:>10.1} {:>10}", format!("{:?}", strategy), tally.games,
+            tally.win_rate() * 100.0, tally.average_points(), tally.average_moves(), format!("{:.1?}", tally.time_per_move()))
+    }));
+
+    lines.join("\n")
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::board::{Board, FirstMove};
+    use crate::core::game::{Difficulty, Mode, Rules};
+    use crate::core::turn::TurnOrder;
+
+    fn new_game(mode: Mode, turn_order: TurnOrder) -> impl Fn(u64) -> Game {
+        with_rules(Rules::PointsOnly, mode, turn_order)
+    }
+
+    fn with_rules(rules: Rules, mode: Mode, turn_order: TurnOrder) -> impl Fn(u64) -> Game {
+        move |seed| Game::from_board(Board::new_deferred(8, 8, Difficulty::Medium, seed, FirstMove::SafeOpening))
+            .with_rules(rules)
+            .with_mode(mode)
+            .with_turn_order(turn_order, false)
+    }
+
+    fn without_timings(tallies: Vec<(Strategy, Tally)>) -> Vec<(Strategy, Tally)> {
+        tallies.into_iter().map(|(strategy, tally)| (strategy, Tally { thinking: Duration::ZERO, ..tally })).collect()
+    }
+
+    #[test]
+    fn every_game_is_counted() {
+        let tallies = run(new_game(Mode::SharedBoard, TurnOrder::RoundRobin), &Strategy::ALL, 20, 0);
+
+        assert_eq!(tallies.len(), 3);
+        assert!(tallies.iter().all(|(_, tally)| tally.games == 20 && tally.moves > 0));
+        // points only: every game ends with at least one winner
+        assert!(tallies.iter().map(|(_, tally)| tally.wins).sum::<u32>() >= 20);
+    }
+
+    #[test]
+    fn lost_games_have_no_winners() {
+        let new_game = with_rules(Rules::Classic, Mode::SharedBoard, TurnOrder::RoundRobin);
+        let tallies = run(&new_game, &[Strategy::Random], 20, 0);
+
+        let won = (0..20u64).filter(|seed| {
+            let mut game = new_game(*seed);
+            let mut bots = Bots::new();
+            let bot = Player::new("Random bot 1".to_string());
+            bots.insert(bot.id, Strategy::Random);
+            game = game.add_player(bot);
+
+            play_bots(game, &bots).0.status == GameStatus::Won
+        }).count() as u32;
+
+        assert!(won < 20);
+        assert_eq!(tallies[0].1.wins, won);
+    }
+
+    #[test]
+    fn time_per_move_over_many_moves() {
+        let tally = Tally { moves: u32::MAX as u64 * 2, thinking: Duration::from_secs(u32::MAX as u64 * 2), ..Tally::default() };
+
+        assert_eq!(tally.time_per_move(), Duration::from_secs(1));
+        assert_eq!(Tally::default().time_per_move(), Duration::ZERO);
+    }
+
+    #[test]
+    fn runs_repeat_with_the_same_seeds() {
+        let tallies = || without_timings(run(new_game(Mode::ParallelBoards, TurnOrder::Simultaneous), &[Strategy::Random, Strategy::Safe], 10, 42));
+
+        assert_eq!(tallies(), tallies());
+    }
+
+    #[test]
+    fn same_strategy_shares_a_row() {
+        let tallies = run(new_game(Mode::SharedBoard, TurnOrder::RoundRobin), &[Strategy::Safe, Strategy::Safe], 5, 0);
+
+        assert_eq!(tallies.len(), 1);
+        assert_eq!(tallies[0].1.games, 10);
+        assert_eq!(table(&tallies).lines().count(), 2);
+    }
+}
@@ -0,0 +1,237 @@
+// Terminal client for a game hosted by multiplayer::server
+//
+// One thread prints whatever the host sends as it arrives; the other reads the player's input.
+
+use std::io::{self, BufReader};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+
+use crate::core::game::GameStatus;
+use crate::core::player::{Player, PlayerId};
+use crate::multiplayer::protocol::*;
+use crate::single_player::text_ui::{parse_action, parse_coordinate, ParseErr};
+
+pub struct Client {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+    pub player_id: PlayerId,
+}
+
+impl Client {
+    // Connects and waits until the host has accepted the player
+    pub fn join(addr: impl ToSocketAddrs, name: &str) -> io::Result<Client> {
+        let writer = TcpStream::connect(addr)?;
+        let reader = BufReader::new(writer.try_clone()?);
+        let mut client = Client { reader, writer, player_id: 0 };
+
+        client.send(&ClientMsg::Join { name: name.to_string() })?;
+
+        match client.recv()? {
+            Some(ServerMsg::Joined { player_id }) => Ok(Client { player_id, ..client }),
+            Some(ServerMsg::Error { message }) => Err(io::Error::other(message)),
+            _ => Err(io::Error::new(io::ErrorKind::InvalidData, "expected the host to accept the join")),
+        }
+    }
+
+    pub fn send(&mut self, message: &ClientMsg) -> io::Result<()> {
+        write_message(&mut self.writer, message)
+    }
+
+    // Returns None once the host has closed the connection
+    pub fn recv(&mut self) -> io::Result<Option<ServerMsg>> {
+        read_message(&mut self.reader)
+    }
+}
+
+// What a player can type while connected
+// For example,
+//   3,5 Reveal
+//   say good luck
+pub fn parse_input(player_input: &str) -> Result<ClientMsg, ParseErr> {
+    if let Some(text) = player_input.trim().strip_prefix("say ") {
+        return Ok(ClientMsg::Chat { text: text.trim().to_string() });
+    }
+
+    match player_input.split_whitespace().collect::<Vec<&str>>()[..] {
+        [coordinate, action] => Ok(ClientMsg::Action {
+            coordinate: parse_coordinate(coordinate)?,
+            action: parse_action(action.to_string())?,
+        }),
+        _ => Err(ParseErr::ParsingFailed),
+    }
+}
+
+// Prints messages from the host, keeping the player list of the latest state for names
+struct Screen {
+    player_id: PlayerId,
+    players: Vec<Player>,
+}
+
+impl Screen {
+    fn name(&self, player_id: PlayerId) -> String {
+        self.players.iter()
+            .find(|player| player.id == player_id)
+            .map(|player| player.name.clone())
+            .unwrap_or_else(|| format!("player {}", player_id))
+    }
+
+    fn show(&mut self, message: &ServerMsg) {
+        match message {
+            ServerMsg::Joined { player_id } => println!("joined as player {}", player_id),
+            ServerMsg::State(state) => {
+                self.players = state.players.clone();
+
+                if let Some(player_action) = state.last_move {
+                    println!("{}'s move: {:?} {},{}", self.name(player_action.player_id), player_action.action,
+                        player_action.coordinate.x, player_action.coordinate.y);
+                }
+
+                state.view().print();
+                self.players.iter().for_each(|player| println!("{}: {}", player.name, player.points));
+                println!();
+
+                match state.current_player {
+                    _ if state.status != GameStatus::Continue => {},
+                    _ if state.waiting_for_players => println!("waiting for players"),
+                    Some(player_id) if player_id == self.player_id => println!("Your turn: x,y Reveal|Flag|Unflag|Chord (or say <message>)"),
+                    Some(player_id) => println!("{}'s turn", self.name(player_id)),
+                    None => println!("Race! Move any time: x,y Reveal|Flag|Unflag|Chord (or say <message>)"),
+                }
+            },
+            ServerMsg::Chat { name, text, .. } => println!("[{}] {}", name, text),
+            ServerMsg::Error { message } => println!("{}. Try again.", message),
+            ServerMsg::GameOver { status, winners, ranking } => {
+                ranking.iter().enumerate()
+                    .for_each(|(place, player)| println!("{}. {}: {}", place + 1, player.name, player.points));
+                match status {
+                    GameStatus::Lost(player_id, coordinate) => {
+                        println!("{} revealed a mine at {},{}. Game over!", self.name(*player_id), coordinate.x, coordinate.y)
+                    },
+                    _ => {
+                        winners.iter().for_each(|winner| print!("{} ", winner.name));
+                        println!("won!");
+                    },
+                }
+                println!("Press Enter to quit.");
+            },
+        }
+    }
+}
+
+// Joins the game at `addr` and plays it from this terminal
+pub fn join_game(addr: &str, name: &str) -> io::Result<()> {
+    match Client::join(addr, name) {
+        Ok(client) => play(client),
+        Err(io_err) => {
+            eprintln!("Could not join the game at {}: {}", addr, io_err);
+            Ok(())
+        }
+    }
+}
+
+// Plays from this terminal until the game is over or the host goes away
+pub fn play(client: Client) -> io::Result<()> {
+    let Client { mut reader, mut writer, player_id } = client;
+    let finished = Arc::new(AtomicBool::new(false));
+
+    let receiver_finished = Arc::clone(&finished);
+    let receiver = thread::spawn(move || {
+        let mut screen = Screen { player_id, players: Vec::new() };
+
+        while let Ok(Some(message)) = read_message::<ServerMsg>(&mut reader) {
+            screen.show(&message);
+            if matches!(message, ServerMsg::GameOver { .. }) {
+                break;
+            }
+        }
+
+        receiver_finished.store(true, Ordering::Relaxed);
+    });
+
+    while !finished.load(Ordering::Relaxed) {
+        let mut player_input = String::new();
+        if io::stdin().read_line(&mut player_input)? == 0 {
+            break;
+        }
+        if finished.load(Ordering::Relaxed) || player_input.trim() == "quit" {
+            break;
+        }
+
+        match parse_input(&player_input) {
+            Ok(message) => write_message(&mut writer, &message)?,
+            Err(parse_err) => println!("{}. Try again.", parse_err),
+        }
+    }
+
+    // leaving forfeits the game
+    let _ = writer.shutdown(std::net::Shutdown::Both);
+    let _ = receiver.join();
+    Ok(())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+    use crate::core::board::{Board, Coordinate};
+    use crate::core::game::{Game, Rules};
+    use crate::core::player::Action;
+    use crate::multiplayer::server::Server;
+
+    #[test]
+    fn parse_move() {
+        assert_eq!(parse_input("3,5 Reveal\n").unwrap(), ClientMsg::Action { coordinate: Coordinate{ x: 3, y: 5 }, action: Action::Reveal });
+        assert_eq!(parse_input("say good luck\n").unwrap(), ClientMsg::Chat { text: "good luck".to_string() });
+        assert!(parse_input("3,5\n").is_err());
+        assert!(parse_input("3,5 Dig\n").is_err());
+    }
+
+    // Hosts a two player game on a 3x3 board with a mine at (0,0) in this process
+    fn host() -> std::net::SocketAddr {
+        let server = Server::bind("127.0.0.1:0").unwrap();
+        let addr = server.local_addr().unwrap();
+
+        thread::spawn(move || {
+            let game = Game::from_board(Board::new_test(3, 3, HashSet::from([Coordinate{ x: 0, y: 0 }])))
+                .with_rules(Rules::Classic);
+            server.run(game, 2)
+                .map(|game| game.status)
+                .unwrap()
+        });
+
+        addr
+    }
+
+    #[test]
+    fn sees_other_players_moves() {
+        let addr = host();
+        let mut alice = Client::join(addr, "alice").unwrap();
+        let mut bob = Client::join(addr, "bob").unwrap();
+
+        let (first, second) = if alice.player_id < bob.player_id { (&mut alice, &mut bob) } else { (&mut bob, &mut alice) };
+        first.send(&parse_input("1,1 Reveal").unwrap()).unwrap();
+
+        let last_move = loop {
+            match second.recv().unwrap() {
+                Some(ServerMsg::State(state)) if state.last_move.is_some() => break state.last_move.unwrap(),
+                Some(_) => continue,
+                None => panic!("the host should still be running"),
+            }
+        };
+
+        assert_eq!(last_move.player_id, first.player_id);
+        assert_eq!(last_move.coordinate, Coordinate{ x: 1, y: 1 });
+    }
+
+    #[test]
+    fn join_after_start() {
+        let addr = host();
+        let _alice = Client::join(addr, "alice").unwrap();
+        let _bob = Client::join(addr, "bob").unwrap();
+
+        assert!(Client::join(addr, "carol").is_err());
+    }
+}
@@ -1 +1,5 @@
-pub mod simulation;
\ No newline at end of file
+pub mod simulation;
+pub mod protocol;
+pub mod server;
+pub mod client;
+pub mod benchmark;
@@ -0,0 +1,128 @@
+// Network protocol: one JSON message per line
+//
+// A client sends Join once, then Action and Chat messages. The host answers a join with Joined,
+// sends every player a State after each change, relays chat and ends with GameOver.
+
+use std::io::{self, BufRead, Write};
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::core::board::{Coordinate, TileStatus};
+use crate::core::game::{Game, GameStatus};
+use crate::core::player::{Action, Player, PlayerAction, PlayerId};
+use crate::core::view::PlayerView;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ClientMsg {
+    Join { name: String },
+    Action { coordinate: Coordinate, action: Action }, // the host knows who sent it
+    Chat { text: String },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ServerMsg {
+    Joined { player_id: PlayerId },
+    State(GameState),
+    Chat { player_id: PlayerId, name: String, text: String },
+    GameOver { status: GameStatus, winners: Vec<Player>, ranking: Vec<Player> }, // ranking is best first
+    Error { message: String }, // only sent to the client whose message was rejected
+}
+
+// What one player is shown of the game
+// Like SavedGame, the board map travels as a plain list
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GameState {
+    pub h_size: u32,
+    pub v_size: u32,
+    pub num_mines: u32,
+    tiles: Vec<(Coordinate, TileStatus)>,
+    pub players: Vec<Player>,
+    pub status: GameStatus,
+    pub current_player: Option<PlayerId>, // None in a race
+    pub last_move: Option<PlayerAction>,
+    pub waiting_for_players: bool,
+}
+
+impl GameState {
+    pub fn new(game: &Game, view: &PlayerView, last_move: Option<PlayerAction>) -> GameState {
+        let mut tiles: Vec<(Coordinate, TileStatus)> = view.iter().map(|(c, tile_status)| (*c, tile_status.clone())).collect();
+        tiles.sort_by_key(|(c, _)| (c.y, c.x));
+
+        let mut players: Vec<Player> = game.players.values().cloned().collect();
+        players.sort_by_key(|player| player.id);
+
+        GameState {
+            h_size: view.h_size,
+            v_size: view.v_size,
+            num_mines: view.num_mines,
+            tiles,
+            players,
+            status: game.status,
+            current_player: game.turns.current_player(),
+            last_move,
+            waiting_for_players: false,
+        }
+    }
+
+    pub fn view(&self) -> PlayerView {
+        PlayerView::new(self.h_size, self.v_size, self.num_mines, self.tiles.iter().cloned().collect())
+    }
+}
+
+pub fn write_message<T: Serialize>(writer: &mut impl Write, message: &T) -> io::Result<()> {
+    let json = serde_json::to_string(message).expect("a message should always serialize");
+    writeln!(writer, "{}", json)?;
+    writer.flush()
+}
+
+// Returns None once the other side has closed the connection
+pub fn read_message<T: DeserializeOwned>(reader: &mut impl BufRead) -> io::Result<Option<T>> {
+    let mut line = String::new();
+    if reader.read_line(&mut line)? == 0 {
+        return Ok(None);
+    }
+
+    serde_json::from_str(&line)
+        .map(Some)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+    use crate::core::board::Board;
+
+    #[test]
+    fn state_round_trips_through_json() {
+        let game = Game::from_board(Board::new_test(3, 3, HashSet::from([Coordinate{ x: 0, y: 0 }])))
+            .add_player(Player::new_with_id(1, "hyeyoung"));
+        let message = ServerMsg::State(GameState::new(&game, &game.view(), None));
+
+        let mut buffer = Vec::new();
+        write_message(&mut buffer, &message).unwrap();
+        let read_back: ServerMsg = read_message(&mut buffer.as_slice()).unwrap().unwrap();
+
+        assert_eq!(read_back, message);
+        match read_back {
+            ServerMsg::State(state) => assert_eq!(state.view(), game.view()),
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn end_of_stream() {
+        let mut empty: &[u8] = &[];
+
+        assert!(read_message::<ClientMsg>(&mut empty).unwrap().is_none());
+    }
+
+    #[test]
+    fn malformed_line() {
+        let mut line: &[u8] = b"{\"Join\":{}}\n";
+
+        assert!(read_message::<ClientMsg>(&mut line).is_err());
+    }
+}
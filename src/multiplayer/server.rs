@@ -0,0 +1,446 @@
+// Hosts a game for players connecting over TCP
+//
+// Game shares its mines through an Rc, so it stays on the thread that calls `run`.
+// One thread accepts connections and one thread per connection reads its messages;
+// they all hand events to the game thread through a channel.
+
+use std::collections::HashMap;
+use std::io::{self, BufReader};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::mpsc::{self, Sender};
+use std::thread;
+
+use crate::core::game::{Game, GameStatus};
+use crate::core::player::{Action, Player, PlayerAction, PlayerId};
+use crate::core::turn::TurnOrder;
+use crate::core::validation::{validate_action, validate_coordinate, InvalidErr};
+use crate::multiplayer::protocol::*;
+use crate::single_player::text_ui::announce_winners;
+
+type ConnectionId = u32;
+
+enum Event {
+    Connected(ConnectionId, TcpStream),
+    Message(ConnectionId, ClientMsg),
+    Malformed(ConnectionId, String),
+    Disconnected(ConnectionId),
+}
+
+struct Connection {
+    stream: TcpStream,
+    player_id: Option<PlayerId>, // set once the client has joined
+}
+
+pub struct Server {
+    listener: TcpListener,
+}
+
+// The game thread's state
+struct Host {
+    game: Game,
+    num_players: usize,
+    connections: HashMap<ConnectionId, Connection>,
+}
+
+impl Server {
+    pub fn bind(addr: impl ToSocketAddrs) -> io::Result<Server> {
+        Ok(Server { listener: TcpListener::bind(addr)? })
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    // Hosts `game` until it is over, or until every player has left
+    // Play starts once `num_players` players have joined
+    pub fn run(self, game: Game, num_players: usize) -> io::Result<Game> {
+        let (sender, events) = mpsc::channel();
+        let listener = self.listener;
+        thread::spawn(move || accept(listener, sender));
+
+        let mut host = Host { game, num_players, connections: HashMap::new() };
+
+        for event in events.iter() {
+            match event {
+                Event::Connected(connection_id, stream) => {
+                    host.connections.insert(connection_id, Connection { stream, player_id: None });
+                },
+                Event::Message(connection_id, message) => host.handle(connection_id, message),
+                Event::Malformed(connection_id, message) => host.send(connection_id, &ServerMsg::Error { message }),
+                Event::Disconnected(connection_id) => host.leave(connection_id),
+            }
+
+            if host.game.status != GameStatus::Continue {
+                let winners: Vec<Player> = host.game.get_winners().into_iter().cloned().collect();
+                let ranking: Vec<Player> = host.game.ranking().into_iter().cloned().collect();
+                host.broadcast(&ServerMsg::GameOver { status: host.game.status, winners, ranking });
+                break;
+            }
+
+            if host.started() && host.connections.values().all(|connection| connection.player_id.is_none()) {
+                break;
+            }
+        }
+
+        Ok(host.game)
+    }
+}
+
+// Hosts `game` on `addr` from the console, announcing the result at the end
+pub fn host_game(addr: &str, game: Game, num_players: usize) -> io::Result<Game> {
+    let server = Server::bind(addr)?;
+    println!("waiting for {} players on {} (seed: {})", num_players, server.local_addr()?, game.seed());
+
+    let game = server.run(game, num_players)?;
+    if game.status == GameStatus::Continue {
+        println!("Everyone left the game.");
+    } else {
+        announce_winners(&game);
+    }
+
+    Ok(game)
+}
+
+fn accept(listener: TcpListener, sender: Sender<Event>) {
+    for (connection_id, stream) in (1..).zip(listener.incoming()) {
+        let Ok(stream) = stream else { continue };
+        let Ok(writer) = stream.try_clone() else { continue };
+
+        // the game thread has finished
+        if sender.send(Event::Connected(connection_id, writer)).is_err() {
+            return;
+        }
+
+        let sender = sender.clone();
+        thread::spawn(move || read_messages(connection_id, stream, sender));
+    }
+}
+
+fn read_messages(connection_id: ConnectionId, stream: TcpStream, sender: Sender<Event>) {
+    let mut reader = BufReader::new(stream);
+
+    loop {
+        let event = match read_message::<ClientMsg>(&mut reader) {
+            Ok(Some(message)) => Event::Message(connection_id, message),
+            Err(err) if err.kind() == io::ErrorKind::InvalidData => Event::Malformed(connection_id, err.to_string()),
+            Ok(None) | Err(_) => {
+                let _ = sender.send(Event::Disconnected(connection_id));
+                return;
+            }
+        };
+
+        if sender.send(event).is_err() {
+            return;
+        }
+    }
+}
+
+impl Host {
+    fn started(&self) -> bool {
+        self.game.players.len() >= self.num_players
+    }
+
+    fn player_id(&self, connection_id: ConnectionId) -> Option<PlayerId> {
+        self.connections.get(&connection_id).and_then(|connection| connection.player_id)
+    }
+
+    fn handle(&mut self, connection_id: ConnectionId, message: ClientMsg) {
+        match message {
+            ClientMsg::Join { name } => self.join(connection_id, name),
+            ClientMsg::Action { coordinate, action } => {
+                let Some(player_id) = self.player_id(connection_id) else {
+                    return self.reject(connection_id, InvalidErr::InvalidPlayer);
+                };
+
+                if !self.started() {
+                    return self.send(connection_id, &ServerMsg::Error { message: "Waiting for players to join".to_string() });
+                }
+                // no message carries a hint back, so a client would pay for nothing
+                if action == Action::Hint {
+                    return self.send(connection_id, &ServerMsg::Error { message: "Hints are only given at the keyboard".to_string() });
+                }
+
+                let player_action = PlayerAction { player_id, coordinate, action };
+                match self.play(&player_action) {
+                    Ok(updated_game) => {
+                        self.game = updated_game;
+                        self.broadcast_state(Some(player_action));
+                    },
+                    Err(invalid_err) => self.reject(connection_id, invalid_err),
+                }
+            },
+            ClientMsg::Chat { text } => {
+                let Some(player_id) = self.player_id(connection_id) else {
+                    return self.reject(connection_id, InvalidErr::InvalidPlayer);
+                };
+
+                let name = self.game.get_player(&player_id).name.clone();
+                self.broadcast(&ServerMsg::Chat { player_id, name, text });
+            },
+        }
+    }
+
+    fn join(&mut self, connection_id: ConnectionId, name: String) {
+        if self.player_id(connection_id).is_some() {
+            return self.send(connection_id, &ServerMsg::Error { message: "Already joined".to_string() });
+        }
+        if self.started() {
+            return self.send(connection_id, &ServerMsg::Error { message: "The game has already started".to_string() });
+        }
+
+        let player = Player::new(name);
+        let player_id = player.id;
+        self.game = self.game.clone().add_player(player);
+
+        if let Some(connection) = self.connections.get_mut(&connection_id) {
+            connection.player_id = Some(player_id);
+        }
+
+        self.send(connection_id, &ServerMsg::Joined { player_id });
+        self.broadcast_state(None);
+    }
+
+    // The same checks the text UI runs on typed input, then the turn check in Game::update
+    // In a race the player may have chosen the move before someone else took the tile,
+    // and Game::update tells those moves apart from invalid ones
+    fn play(&self, player_action: &PlayerAction) -> Result<Game, InvalidErr> {
+        if self.game.turns.order == TurnOrder::Simultaneous {
+            return self.game.update(player_action);
+        }
+
+        let view = self.game.player_view(&player_action.player_id);
+        let player = self.game.get_player(&player_action.player_id);
+
+        validate_coordinate(&view, &player_action.coordinate, player)
+            .and_then(|coordinate| validate_action(&view, *player_action, &coordinate))
+            .and_then(|player_action| self.game.update(&player_action))
+    }
+
+    fn leave(&mut self, connection_id: ConnectionId) {
+        let Some(connection) = self.connections.remove(&connection_id) else { return };
+
+        if let Some(player_id) = connection.player_id {
+            self.game = if self.started() { self.game.forfeit(&player_id) } else { self.game.clone().remove_player(&player_id) };
+            self.broadcast_state(None);
+        }
+    }
+
+    fn reject(&mut self, connection_id: ConnectionId, invalid_err: InvalidErr) {
+        self.send(connection_id, &ServerMsg::Error { message: invalid_err.to_string() });
+    }
+
+    // A failed write means the client is gone; its reader thread reports the disconnect
+    fn send(&mut self, connection_id: ConnectionId, message: &ServerMsg) {
+        if let Some(connection) = self.connections.get_mut(&connection_id) {
+            let _ = write_message(&mut connection.stream, message);
+        }
+    }
+
+    fn broadcast(&mut self, message: &ServerMsg) {
+        for connection in self.connections.values_mut().filter(|connection| connection.player_id.is_some()) {
+            let _ = write_message(&mut connection.stream, message);
+        }
+    }
+
+    // Every player gets their own view of the board
+    fn broadcast_state(&mut self, last_move: Option<PlayerAction>) {
+        let waiting_for_players = !self.started();
+        let game = &self.game;
+
+        for connection in self.connections.values_mut() {
+            let Some(player_id) = connection.player_id else { continue };
+            let mut state = GameState::new(game, &game.player_view(&player_id), last_move);
+            state.waiting_for_players = waiting_for_players;
+            let _ = write_message(&mut connection.stream, &ServerMsg::State(state));
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+    use crate::core::board::{Board, Coordinate};
+    use crate::core::game::Rules;
+
+    struct TestClient {
+        reader: BufReader<TcpStream>,
+        writer: TcpStream,
+    }
+
+    impl TestClient {
+        fn join(addr: SocketAddr, name: &str) -> (TestClient, PlayerId) {
+            let writer = TcpStream::connect(addr).unwrap();
+            let mut client = TestClient { reader: BufReader::new(writer.try_clone().unwrap()), writer };
+            client.send(&ClientMsg::Join { name: name.to_string() });
+
+            match client.recv() {
+                ServerMsg::Joined { player_id } => (client, player_id),
+                message => panic!("expected Joined, got {:?}", message),
+            }
+        }
+
+        fn send(&mut self, message: &ClientMsg) {
+            write_message(&mut self.writer, message).unwrap();
+        }
+
+        fn recv(&mut self) -> ServerMsg {
+            read_message(&mut self.reader).unwrap().expect("the server should still be connected")
+        }
+
+        // Skips the state updates sent until the first message that is not one
+        fn recv_skipping_states(&mut self) -> ServerMsg {
+            loop {
+                match self.recv() {
+                    ServerMsg::State(_) => continue,
+                    message => return message,
+                }
+            }
+        }
+
+        fn recv_state(&mut self) -> GameState {
+            match self.recv() {
+                ServerMsg::State(state) => state,
+                message => panic!("expected State, got {:?}", message),
+            }
+        }
+    }
+
+    // A two player game on a 3x3 board with a mine at (0,0)
+    // The Game is built on the server thread, which hands back only the final status
+    fn start_server(turn_order: TurnOrder) -> (SocketAddr, thread::JoinHandle<GameStatus>) {
+        let server = Server::bind("127.0.0.1:0").unwrap();
+        let addr = server.local_addr().unwrap();
+
+        let handle = thread::spawn(move || {
+            let game = Game::from_board(Board::new_test(3, 3, HashSet::from([Coordinate{ x: 0, y: 0 }])))
+                .with_rules(Rules::Classic)
+                .with_turn_order(turn_order, false);
+            server.run(game, 2).unwrap().status
+        });
+
+        (addr, handle)
+    }
+
+    fn action(x: u32, y: u32, action: Action) -> ClientMsg {
+        ClientMsg::Action { coordinate: Coordinate{ x, y }, action }
+    }
+
+    #[test]
+    fn play_over_loopback() {
+        let (addr, handle) = start_server(TurnOrder::RoundRobin);
+        let (mut alice, alice_id) = TestClient::join(addr, "alice");
+        alice.recv_state();
+        let (mut bob, bob_id) = TestClient::join(addr, "bob");
+        alice.recv_state();
+
+        let state = bob.recv_state();
+        assert_eq!(state.players.len(), 2);
+        assert_eq!(state.current_player, Some(alice_id.min(bob_id)));
+
+        let (first, second) = if alice_id < bob_id { (&mut alice, &mut bob) } else { (&mut bob, &mut alice) };
+
+        // out of turn
+        second.send(&action(1, 1, Action::Reveal));
+        assert!(matches!(second.recv(), ServerMsg::Error { .. }));
+
+        // out of bounds
+        first.send(&action(5, 5, Action::Reveal));
+        assert!(matches!(first.recv(), ServerMsg::Error { .. }));
+
+        first.send(&action(1, 1, Action::Reveal));
+        let state = second.recv_state();
+        assert_eq!(state.last_move.map(|player_action| player_action.coordinate), Some(Coordinate{ x: 1, y: 1 }));
+        assert_eq!(state.status, GameStatus::Continue);
+        first.recv_state();
+
+        // revealing the far corner opens every safe tile
+        second.send(&action(2, 2, Action::Reveal));
+        match first.recv_skipping_states() {
+            ServerMsg::GameOver { status, .. } => assert_eq!(status, GameStatus::Won),
+            message => panic!("expected GameOver, got {:?}", message),
+        }
+
+        assert_eq!(handle.join().unwrap(), GameStatus::Won);
+    }
+
+    #[test]
+    fn race_over_loopback() {
+        let (addr, _handle) = start_server(TurnOrder::Simultaneous);
+        let (mut alice, _) = TestClient::join(addr, "alice");
+        let (mut bob, bob_id) = TestClient::join(addr, "bob");
+        while bob.recv_state().players.len() < 2 {}
+
+        // bob does not wait for a turn
+        bob.send(&action(1, 1, Action::Reveal));
+        let state = bob.recv_state();
+        assert_eq!(state.last_move.map(|player_action| player_action.player_id), Some(bob_id));
+
+        // alice chose the same tile before she saw bob's move
+        alice.send(&action(1, 1, Action::Reveal));
+        assert_eq!(alice.recv_skipping_states(), ServerMsg::Error { message: InvalidErr::TileTaken.to_string() });
+    }
+
+    #[test]
+    fn chat_is_relayed() {
+        let (addr, _handle) = start_server(TurnOrder::RoundRobin);
+        let (mut alice, alice_id) = TestClient::join(addr, "alice");
+        let (mut bob, _) = TestClient::join(addr, "bob");
+
+        alice.send(&ClientMsg::Chat { text: "good luck".to_string() });
+
+        assert_eq!(bob.recv_skipping_states(), ServerMsg::Chat { player_id: alice_id, name: "alice".to_string(), text: "good luck".to_string() });
+    }
+
+    #[test]
+    fn action_before_everyone_joined() {
+        let (addr, _handle) = start_server(TurnOrder::RoundRobin);
+        let (mut alice, _) = TestClient::join(addr, "alice");
+
+        alice.send(&action(1, 1, Action::Reveal));
+
+        assert!(matches!(alice.recv_skipping_states(), ServerMsg::Error { .. }));
+    }
+
+    #[test]
+    fn no_hints_over_the_network() {
+        let (addr, _handle) = start_server(TurnOrder::Simultaneous);
+        let (mut alice, alice_id) = TestClient::join(addr, "alice");
+        let (_bob, _) = TestClient::join(addr, "bob");
+        while alice.recv_state().players.len() < 2 {}
+
+        alice.send(&action(1, 1, Action::Hint));
+        assert!(matches!(alice.recv(), ServerMsg::Error { .. }));
+
+        // the hint cost nothing
+        alice.send(&action(1, 1, Action::Reveal));
+        let state = alice.recv_state();
+        assert_eq!(state.players.iter().find(|player| player.id == alice_id).unwrap().points, 1);
+    }
+
+    #[test]
+    fn malformed_message() {
+        let (addr, _handle) = start_server(TurnOrder::RoundRobin);
+        let (mut alice, _) = TestClient::join(addr, "alice");
+
+        std::io::Write::write_all(&mut alice.writer, b"reveal 1,1\n").unwrap();
+
+        assert!(matches!(alice.recv_skipping_states(), ServerMsg::Error { .. }));
+    }
+
+    #[test]
+    fn leaving_forfeits() {
+        let (addr, handle) = start_server(TurnOrder::RoundRobin);
+        let (alice, _) = TestClient::join(addr, "alice");
+        let (mut bob, bob_id) = TestClient::join(addr, "bob");
+
+        drop(alice);
+        // whoever went first, it is now bob's turn
+        while bob.recv_state().current_player != Some(bob_id) {}
+
+        // the game is over once the last player leaves too
+        drop(bob);
+        assert_eq!(handle.join().unwrap(), GameStatus::Continue);
+    }
+}
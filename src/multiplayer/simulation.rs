@@ -1,32 +1,98 @@
 use std::io;
-use rand::Rng;
+use std::path::Path;
 
 use crate::core::game::{Game};
+use crate::core::controller::{controllers, Bots, Choice};
+use crate::core::player::{Action, Player};
 use crate::core::game::*;
+use crate::core::replay::ReplayLog;
+use crate::core::history::{GameHistory, UndoPolicy};
+use crate::single_player::cli::Setup;
 use crate::single_player::text_ui::*;
 
-pub fn simulate_multiplayer() -> io::Result<Game> {
-    let mut game = start_game();
-    
-    let mut rng = rand::thread_rng();
-    
-    while game.status == GameStatus::Continue {
-        let turn_id = rng.gen_range(1..game.players.len()+1) as u32;
-        let current_player = game.get_player(&turn_id);
+// With `record`, every move is written to a replay log at that path
+pub fn simulate_multiplayer(setup: &Setup, record: Option<&Path>) -> io::Result<Game> {
+    let (game, bots) = start_game(setup)?;
+    resume_multiplayer(game, &bots, setup.multiplayer_undo(), record)
+}
+
+// Plays a new or loaded game until it is over
+// Players compete for points, so `undo` either costs the player who asks or is not allowed
+// `bots` play their own turns; everyone else answers prompts at this keyboard
+pub fn resume_multiplayer(game: Game, bots: &Bots, undo: UndoPolicy, record: Option<&Path>) -> io::Result<Game> {
+    let mut controllers = controllers(&game, bots, || Box::new(Human));
+    let mut log = ReplayLog::new(&game);
+    let mut history = GameHistory::new(game, undo);
+    let mut num_moves = 0;
+
+    while history.game().status == GameStatus::Continue {
+        let game = history.game();
+        let current_player = game.current_player().unwrap_or_else(|| at_keyboard(game, num_moves));
+        let turn_id = current_player.id;
 
         println!("{}'s turn", current_player.name);
+        if game.mode == Mode::ParallelBoards {
+            game.player_view(&turn_id).print();
+        }
+
+        // a bot sees no more than a person would
+        let controller = controllers.get_mut(&turn_id).expect("every player has a controller");
+        let action = match controller.choose(&game.player_view(&turn_id), current_player)? {
+            Choice::Play(action) => action,
+            Choice::Hint => match take_hint(game, &turn_id) {
+                Some(action) => action,
+                None => continue,
+            },
+            Choice::Save(path) => {
+                save_game(game, &path);
+                continue;
+            },
+            choice @ (Choice::Undo | Choice::Redo) => {
+                let command = if choice == Choice::Undo { Command::Undo } else { Command::Redo };
+                match undo_or_redo(&history, &mut log, &command, &turn_id) {
+                    Ok(updated_history) => history = updated_history,
+                    Err(invalid_err) => println!("{}", invalid_err),
+                }
+                continue;
+            }
+        };
 
-        let coordinate = get_coordinate(&game, current_player)?;
-        let action = get_action(&game, current_player, coordinate)?;
+        println!("{}'s move: {:?} {:?}", current_player.name, action.action, action.coordinate);
 
-        println!("{}'s move: {:?} {:?}", current_player.name, action.action, coordinate);
+        let updated_history = match history.update(&action) {
+            Ok(updated_history) => updated_history,
+            Err(invalid_err) => {
+                println!("{}", invalid_err);
+                continue;
+            }
+        };
+        if let Some(warning) = guess_warning(history.game(), updated_history.game(), &turn_id) {
+            println!("{}", warning);
+        }
+        history = updated_history;
+        // a hint keeps the keyboard with the same player
+        if action.action != Action::Hint {
+            num_moves += 1;
+        }
 
-        game = game.update(&action);
-        game.board.print();
-        print_scores(&game);
+        if let Some(path) = record {
+            log.record(&action);
+            log.save(path)?;
+        }
+        history.game().player_view(&turn_id).print();
+        print_scores(history.game());
     }
 
+    let game = history.into_game();
     announce_winners(&game);
 
     Ok(game)
-}
\ No newline at end of file
+}
+
+// Nobody is in turn in a race, so at one keyboard the players pass it on after every move
+pub(crate) fn at_keyboard(game: &Game, num_moves: usize) -> &Player {
+    let mut players = game.active_players();
+    players.sort();
+
+    game.get_player(&players[num_moves % players.len()])
+}
@@ -0,0 +1,308 @@
+// Game settings given on the command line
+//
+// Every setting is optional: start_game prompts for whatever is missing.
+
+use crate::core::board::FirstMove;
+use crate::core::controller::Strategy;
+use crate::core::game::{Difficulty, Mode, Rules};
+use crate::core::history::UndoPolicy;
+use crate::core::turn::TurnOrder;
+use crate::core::validation::{validate_mine_count, BOARD_MAX_SIZE};
+use crate::single_player::text_ui::*;
+
+pub const DEFAULT_ADDR: &str = "127.0.0.1:7878";
+
+// Every flag the binaries take a value for, and the flags that stand alone
+const VALUE_FLAGS: [&str; 20] = ["--mode", "--players", "--name", "--bots", "--width", "--height", "--difficulty", "--mines",
+    "--board", "--rules", "--boards", "--turn-order", "--extra-turn", "--undo-penalty", "--seed", "--addr", "--record", "--debug",
+    "--load", "--replay"];
+const SWITCHES: [&str; 1] = ["--training"];
+
+// Returns the value following `flag`, e.g. `--seed 42`
+pub fn arg_value<'a>(args: &'a [String], flag: &str) -> Option<&'a String> {
+    args.iter().position(|arg| arg == flag).and_then(|i| args.get(i + 1))
+}
+
+// Parses the value of `flag` if it is given, explaining what was expected otherwise
+fn flag_value<T>(args: &[String], flag: &str, parse: impl Fn(&str) -> Result<T, ParseErr>, expected: &str) -> Result<Option<T>, String> {
+    arg_value(args, flag)
+        .map(|value| parse(value).map_err(|_| format!("{} expects {}", flag, expected)))
+        .transpose()
+}
+
+// Anything a binary does not take is an error instead of being ignored, e.g. a misspelled flag
+// `value_flags` are followed by a value, `switches` stand alone
+pub fn check_args(args: &[String], value_flags: &[&str], switches: &[&str]) -> Result<(), String> {
+    let mut args = args.iter();
+
+    while let Some(arg) = args.next() {
+        if value_flags.contains(&arg.as_str()) {
+            args.next().ok_or_else(|| format!("{} expects a value", arg))?;
+        } else if !switches.contains(&arg.as_str()) {
+            return Err(format!("unknown argument: {}", arg));
+        }
+    }
+
+    Ok(())
+}
+
+// What `--mode` launches
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Launch {
+    Single,
+    Multi,  // players take turns at this keyboard
+    Server,
+    Client,
+    Replay,
+}
+
+pub fn parse_launch(input: &str) -> Result<Launch, ParseErr> {
+    match input.trim() {
+        "single" => Ok(Launch::Single),
+        "multi" => Ok(Launch::Multi),
+        "server" => Ok(Launch::Server),
+        "client" => Ok(Launch::Client),
+        "replay" => Ok(Launch::Replay),
+        _ => Err(ParseErr::ParsingFailed)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Setup {
+    pub launch: Option<Launch>,
+    pub num_players: Option<u32>,
+    pub name: Option<String>,             // the first player's name
+    pub h_size: Option<u32>,
+    pub v_size: Option<u32>,
+    pub difficulty: Option<Difficulty>,   // `--mines` gives Difficulty::Custom
+    pub first_move: Option<FirstMove>,
+    pub rules: Option<Rules>,             // only asked for with more than one player
+    pub mode: Option<Mode>,
+    pub turn_order: Option<TurnOrder>,
+    pub extra_turn: Option<bool>,
+    pub undo_penalty: Option<i32>,        // several players at this keyboard may undo for this many points
+    pub seed: Option<u64>,
+    pub training: bool,                   // show mine probabilities on hidden tiles
+    pub bots: Vec<Strategy>,              // the last players, counted in num_players
+}
+
+impl Setup {
+    // For example,
+    //   --mode multi --players 3 --width 16 --height 16 --mines 40 --seed 42
+    pub fn from_args(args: &[String]) -> Result<Setup, String> {
+        Setup::from_args_with(args, &[])
+    }
+
+    // Like from_args, for a binary that also takes `extra_flags`, e.g. the benchmark's `--games`
+    pub fn from_args_with(args: &[String], extra_flags: &[&str]) -> Result<Setup, String> {
+        check_args(args, &[&VALUE_FLAGS[..], extra_flags].concat(), &SWITCHES)?;
+        let number = |input: &str| input.trim().parse::<u32>().map_err(|_| ParseErr::NotNum);
+
+        let launch = match flag_value(args, "--mode", parse_launch, "single, multi, server, client or replay")? {
+            None if arg_value(args, "--replay").is_some() => Some(Launch::Replay),
+            launch => launch,
+        };
+
+        let bots = flag_value(args, "--bots", parse_bots, "a list of random, safe or probability")?.unwrap_or_default();
+        if !bots.is_empty() && matches!(launch, Some(Launch::Server | Launch::Client | Launch::Replay)) {
+            return Err("--bots is for games at this keyboard".to_string());
+        }
+
+        let num_players = match (launch, flag_value(args, "--players", number, "a number of players")?) {
+            (_, Some(num_players)) if num_players == 0 || num_players > MAX_NUM_PLAYERS => {
+                return Err(format!("--players expects 1 to {} players", MAX_NUM_PLAYERS));
+            },
+            (Some(Launch::Single), Some(num_players)) if num_players > 1 => return Err("--mode single is for one player".to_string()),
+            (Some(Launch::Multi), Some(1)) => return Err("--mode multi needs at least 2 players".to_string()),
+            (Some(Launch::Single), None) => Some(1),
+            // one person plays the bots unless --players says otherwise
+            (_, None) if !bots.is_empty() => Some(bots.len() as u32 + 1),
+            (_, num_players) => num_players,
+        };
+        match num_players {
+            Some(num_players) if !bots.is_empty() && num_players > MAX_NUM_PLAYERS => {
+                return Err(format!("at most {} players, bots included", MAX_NUM_PLAYERS));
+            },
+            Some(num_players) if (bots.len() as u32) > num_players => return Err("--players counts the bots too".to_string()),
+            Some(1) if !bots.is_empty() => return Err("a bot needs someone to play against".to_string()),
+            _ => {},
+        }
+
+        let undo_penalty = flag_value(args, "--undo-penalty", |input| {
+            number(input).and_then(|points| i32::try_from(points).map_err(|_| ParseErr::NotNum))
+        }, "a number of points")?;
+        if undo_penalty.is_some() && (num_players == Some(1) || matches!(launch, Some(Launch::Server | Launch::Client | Launch::Replay))) {
+            return Err("--undo-penalty is for several players at this keyboard".to_string());
+        }
+
+        let h_size = flag_value(args, "--width", number, "a positive number")?;
+        let v_size = flag_value(args, "--height", number, "a positive number")?;
+        if h_size == Some(0) || v_size == Some(0) {
+            return Err("the board needs at least one row and one column".to_string());
+        }
+        if let Some((flag, _)) = [("--width", h_size), ("--height", v_size)].into_iter().find(|(_, size)| size.is_some_and(|size| size > BOARD_MAX_SIZE)) {
+            return Err(format!("{} goes up to {}", flag, BOARD_MAX_SIZE));
+        }
+
+        let difficulty = flag_value(args, "--difficulty", |value| parse_difficulty(value.to_string()),
+            "Easy, Medium, Hard, Beginner, Intermediate, Expert, a number of mines or a density such as 0.2")?;
+        let mines = flag_value(args, "--mines", number, "a number of mines")?;
+        let difficulty = match (difficulty, mines) {
+            (Some(_), Some(_)) => return Err("give either --difficulty or --mines".to_string()),
+            (None, Some(mines)) => Some(Difficulty::Custom { mines }),
+            (difficulty, None) => difficulty,
+        };
+
+        let board_size = match (difficulty.and_then(|difficulty| difficulty.board_size()), h_size, v_size) {
+            (Some(_), Some(_), _) | (Some(_), _, Some(_)) => return Err("a classic preset comes with its own board size".to_string()),
+            (Some(board_size), None, None) => Some(board_size),
+            (None, Some(h_size), Some(v_size)) => Some((h_size, v_size)),
+            _ => None,
+        };
+        if let (Some((h_size, v_size)), Some(difficulty)) = (board_size, difficulty) {
+            validate_mine_count(h_size, v_size, difficulty).map_err(|_| "the mines must leave at least one safe tile".to_string())?;
+        }
+
+        Ok(Setup {
+            launch,
+            num_players,
+            name: arg_value(args, "--name").cloned(),
+            h_size,
+            v_size,
+            difficulty,
+            first_move: flag_value(args, "--board", |value| parse_board_kind(value.to_string()), "Classic, SafeTile or NoGuess")?,
+            rules: flag_value(args, "--rules", |value| parse_rules(value.to_string()), "Classic, Elimination or PointsOnly")?,
+            mode: flag_value(args, "--boards", |value| parse_mode(value.to_string()), "Shared or Parallel")?,
+            turn_order: flag_value(args, "--turn-order", |value| parse_turn_order(value.to_string()), "RoundRobin, Shuffled or Race")?,
+            extra_turn: flag_value(args, "--extra-turn", |value| parse_yes_no(value.to_string()), "yes or no")?,
+            undo_penalty,
+            seed: flag_value(args, "--seed", parse_seed, "a non-negative number")?,
+            training: args.iter().any(|arg| arg == "--training"),
+            bots,
+        })
+    }
+
+    // Several players compete for points, so they can only take a move back if it costs them
+    pub fn multiplayer_undo(&self) -> UndoPolicy {
+        match self.undo_penalty {
+            Some(points) => UndoPolicy::Penalized(points),
+            None => UndoPolicy::Disabled,
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn missing_values_stay_unset() {
+        let setup = Setup::from_args(&args("--width 16 --mines 40")).unwrap();
+
+        assert_eq!(setup.h_size, Some(16));
+        assert_eq!(setup.v_size, None);
+        assert_eq!(setup.difficulty, Some(Difficulty::Custom { mines: 40 }));
+        assert_eq!(setup.num_players, None);
+        assert_eq!(Setup::from_args(&args("--difficulty 0.2")).unwrap().difficulty, Some(Difficulty::Density(0.2)));
+        assert_eq!(Setup::from_args(&args("--difficulty Expert")).unwrap().difficulty, Some(Difficulty::Expert));
+        assert_eq!(Setup::from_args(&[]).unwrap(), Setup::default());
+        assert!(Setup::from_args(&args("--training")).unwrap().training);
+        assert_eq!(Setup::from_args(&args("--board SafeTile")).unwrap().first_move, Some(FirstMove::SafeTile));
+    }
+
+    #[test]
+    fn full_setup() {
+        let setup = Setup::from_args(&args("--mode multi --players 3 --width 9 --height 9 --difficulty Hard --board NoGuess \
+            --rules Elimination --boards Parallel --turn-order Race --extra-turn no --seed 42")).unwrap();
+
+        assert_eq!(setup, Setup {
+            launch: Some(Launch::Multi),
+            num_players: Some(3),
+            name: None,
+            h_size: Some(9),
+            v_size: Some(9),
+            difficulty: Some(Difficulty::Hard),
+            first_move: Some(FirstMove::NoGuess),
+            rules: Some(Rules::Elimination),
+            mode: Some(Mode::ParallelBoards),
+            turn_order: Some(TurnOrder::Simultaneous),
+            extra_turn: Some(false),
+            undo_penalty: None,
+            seed: Some(42),
+            training: false,
+            bots: Vec::new(),
+        });
+    }
+
+    #[test]
+    fn single_player_mode() {
+        assert_eq!(Setup::from_args(&args("--mode single")).unwrap().num_players, Some(1));
+        assert!(Setup::from_args(&args("--mode single --players 2")).is_err());
+        assert!(Setup::from_args(&args("--mode multi --players 1")).is_err());
+        assert_eq!(Setup::from_args(&args("--replay game.log")).unwrap().launch, Some(Launch::Replay));
+    }
+
+    #[test]
+    fn bots_take_the_last_seats() {
+        let setup = Setup::from_args(&args("--bots safe,probability")).unwrap();
+        assert_eq!(setup.bots, vec![Strategy::Safe, Strategy::Probability]);
+        assert_eq!(setup.num_players, Some(3));
+
+        assert_eq!(Setup::from_args(&args("--players 2 --bots random,random")).unwrap().num_players, Some(2));
+        assert!(Setup::from_args(&args("--players 2 --bots random,safe,safe")).is_err());
+        assert!(Setup::from_args(&args("--players 1 --bots safe")).is_err());
+        assert!(Setup::from_args(&args("--mode single --bots safe")).is_err());
+        assert!(Setup::from_args(&args("--mode server --bots safe")).is_err());
+        assert!(Setup::from_args(&args("--bots clever")).is_err());
+    }
+
+    #[test]
+    fn undo_costs_points_with_several_players() {
+        let setup = Setup::from_args(&args("--players 2 --undo-penalty 5")).unwrap();
+        assert_eq!(setup.multiplayer_undo(), UndoPolicy::Penalized(5));
+
+        assert_eq!(Setup::from_args(&args("--players 2")).unwrap().multiplayer_undo(), UndoPolicy::Disabled);
+        assert!(Setup::from_args(&args("--mode single --undo-penalty 5")).is_err());
+        assert!(Setup::from_args(&args("--mode server --undo-penalty 5")).is_err());
+        assert!(Setup::from_args(&args("--undo-penalty -5")).is_err());
+    }
+
+    #[test]
+    fn invalid_values() {
+        assert!(Setup::from_args(&args("--mode coop")).is_err());
+        assert!(Setup::from_args(&args("--players 0")).is_err());
+        assert!(Setup::from_args(&args("--width -3")).is_err());
+        assert!(Setup::from_args(&args("--difficulty Easy --mines 3")).is_err());
+        assert!(Setup::from_args(&args("--width 3 --height 3 --mines 9")).is_err());
+        assert!(Setup::from_args(&args("--width 9 --difficulty Expert")).is_err());
+        assert!(Setup::from_args(&args("--width 9 --height 9 --difficulty 1.5")).is_err());
+        assert!(Setup::from_args(&args("--seed abc")).is_err());
+    }
+
+    #[test]
+    fn either_side_too_big() {
+        assert!(Setup::from_args(&args("--width 30 --height 30")).is_ok());
+        assert!(Setup::from_args(&args("--width 1000")).is_err());
+        assert!(Setup::from_args(&args("--width 100000 --height 1")).is_err());
+        assert!(Setup::from_args(&args("--height 31")).is_err());
+    }
+
+    #[test]
+    fn unknown_arguments() {
+        assert!(Setup::from_args(&args("--widht 16")).is_err());
+        assert!(Setup::from_args(&args("16")).is_err());
+        assert!(Setup::from_args(&args("--seed")).is_err());
+        assert!(Setup::from_args(&args("--games 10")).is_err());
+        assert!(Setup::from_args_with(&args("--games 10 --seed 3"), &["--games"]).is_ok());
+        assert!(Setup::from_args(&args("--load game.json --record game.log --debug mines.log --training")).is_ok());
+
+        // the client only takes its own flags
+        assert!(check_args(&args("--addr 127.0.0.1:7878 --name hyeyoung"), &["--addr", "--name"], &[]).is_ok());
+        assert!(check_args(&args("--name hyeyoung --seed 42"), &["--addr", "--name"], &[]).is_err());
+    }
+}
@@ -1,2 +1,4 @@
 pub mod text_ui;
-pub mod simulation;
\ No newline at end of file
+pub mod simulation;
+pub mod tui;
+pub mod cli;
\ No newline at end of file
@@ -1,9 +1,16 @@
 use crate::core::game::{Game, GameStatus};
+use crate::core::replay::ReplayLog;
+use crate::core::history::{GameHistory, UndoPolicy};
+use crate::core::player::PlayerId;
+use crate::single_player::cli::Setup;
 use crate::single_player::text_ui::*;
+use crate::single_player::tui;
 
-use std::io;
+use std::io::{self, IsTerminal};
+use std::path::Path;
 
-pub fn simulate_single_player() -> io::Result<Game> {
+// With `record`, every move is written to a replay log at that path
+pub fn simulate_single_player(setup: &Setup, record: Option<&Path>) -> io::Result<Game> {
     // println!("Let's play minesweeper game!");
 
     // let single_player = Player::new(get_name());
@@ -12,33 +19,103 @@ pub fn simulate_single_player() -> io::Result<Game> {
     // // TODO: validate input here too
     // let game_level = get_difficulty()?;
 
-    // let mut game = Game::new(h_size, v_size, game_level);
+    // let mut game = Game::new(h_size, v_size, game_level, seed);
     
     // game = game.add_player(single_player);
 
     // game.board.print();
 
-    let mut game = start_game();
-    
+    let (game, _) = start_game(&Setup { num_players: Some(1), ..setup.clone() })?;
+    resume_single_player(game, record, setup.training)
+}
+
+// Plays a new or loaded game until it is over
+// A single player practices, so undo and redo are free
+// With `training`, the board shows the chance that each hidden tile is a mine
+pub fn resume_single_player(game: Game, record: Option<&Path>, training: bool) -> io::Result<Game> {
+    // ids come from a global counter, so a loaded player is not necessarily 1
+    let player_id = *game.players.keys().min().expect("a game should have a player");
+    let mut log = ReplayLog::new(&game);
+    let history = GameHistory::new(game, UndoPolicy::Free);
+
+    // piped input, e.g. a scripted game, still goes through the prompts
+    let history = if io::stdin().is_terminal() && io::stdout().is_terminal() {
+        tui::play(history, &mut log, record, player_id, training)?
+    } else {
+        play_with_prompts(history, &mut log, record, player_id, training)?
+    };
+
+    let game = history.into_game();
+    if game.status == GameStatus::Continue {
+        println!("Game stopped.");
+    } else {
+        announce_winners(&game);
+    }
+
+    Ok(game)
+}
+
+fn play_with_prompts(mut history: GameHistory, log: &mut ReplayLog, record: Option<&Path>, player_id: PlayerId, training: bool) -> io::Result<GameHistory> {
     ////////// interactive game loop //////////
-    while game.status == GameStatus::Continue {
-        // 1. get player's coordinate
-        let player_coordinate = get_coordinate(&game, &game.get_player(&1))?;
-        println!("player coordinate: {:?}", player_coordinate);
-        
-        // 2. get player's action
-        let player_action = get_action(&game, &game.get_player(&1), player_coordinate)?;
-        println!("player action: {:?}", player_action);
+    while history.game().status == GameStatus::Continue {
+        let game = history.game();
+        let view = game.player_view(&player_id);
+
+        // 1. get player's coordinate and action, a hint, or undo/redo
+        let player_action = match get_coordinate(&view, game.get_player(&player_id))? {
+            Command::Coordinate(player_coordinate) => {
+                println!("player coordinate: {:?}", player_coordinate);
+
+                // 2. get player's action
+                let player_action = get_action(&view, game.get_player(&player_id), player_coordinate)?;
+                println!("player action: {:?}", player_action);
+                player_action
+            },
+            Command::Hint => match take_hint(game, &player_id) {
+                Some(player_action) => player_action,
+                None => continue,
+            },
+            Command::Save(path) => {
+                save_game(game, &path);
+                continue;
+            },
+            command => {
+                history = match undo_or_redo(&history, log, &command, &player_id) {
+                    Ok(updated_history) => updated_history,
+                    Err(invalid_err) => {
+                        println!("{}", invalid_err);
+                        continue;
+                    }
+                };
+
+                print_view(&history.game().view(), training);
+                print_scores(history.game());
+                continue;
+            }
+        };
 
         // 3. update the game
-        game = game.update(&player_action);
+        let updated_history = match history.update(&player_action) {
+            Ok(updated_history) => updated_history,
+            Err(invalid_err) => {
+                println!("{}", invalid_err);
+                continue;
+            }
+        };
+        if let Some(warning) = guess_warning(history.game(), updated_history.game(), &player_id) {
+            println!("{}", warning);
+        }
+        history = updated_history;
+
+        if let Some(path) = record {
+            log.record(&player_action);
+            log.save(path)?;
+        }
 
         // 4. print board
-        game.board.print();
-        print_scores(&game);
+        print_view(&history.game().view(), training);
+        print_scores(history.game());
     }
 
-    announce_winners(&game);
-
-    Ok(game)
-}
\ No newline at end of file
+    Ok(history)
+}
@@ -1,11 +1,32 @@
-use crate::core::board::{Coordinate};
-use crate::core::game::{Game, Difficulty};
-use crate::core::player::{Player, Action, PlayerAction};
+use crate::core::board::{Board, Coordinate, FirstMove};
+use crate::core::game::{Game, GameStatus, Difficulty, Mode, Rules, HINT_COST};
+use crate::core::player::{Player, PlayerId, Action, PlayerAction};
 use crate::core::validation::{InvalidErr, CoordinateErr};
 use crate::core::validation::*;
+use crate::core::save::LoadErr;
+use crate::core::replay::ReplayLog;
+use crate::core::history::GameHistory;
+use crate::core::turn::TurnOrder;
+use crate::core::view::PlayerView;
+use crate::core::probability::mine_probabilities;
+use crate::core::hint::{hint, Hint, Verdict};
+use crate::core::controller::{Bots, Choice, PlayerController, Strategy};
+use crate::single_player::cli::Setup;
 
 use std::io;
 use std::fmt;
+use std::path::{Path, PathBuf};
+
+// Reads a line from the console
+// Running out of input, e.g. at the end of a piped script, is an error instead of asking forever
+fn read_input() -> io::Result<String> {
+    let mut player_input = String::new();
+    if io::stdin().read_line(&mut player_input)? == 0 {
+        return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "no more input"));
+    }
+
+    Ok(player_input)
+}
 
 macro_rules! try_again {
     ($e: expr) => {{
@@ -36,8 +57,16 @@ impl fmt::Display for InvalidErr {
         match self {
             InvalidErr::InvalidAction => write!(f, "Invalid action"),
             InvalidErr::InvalidPlayer => write!(f, "Invalid player"),
+            InvalidErr::GameOver => write!(f, "The game is over"),
+            InvalidErr::NotYourTurn => write!(f, "It is not your turn"),
+            InvalidErr::TileTaken => write!(f, "Another player got to that tile first"),
+            InvalidErr::NoMinesYet => write!(f, "Reveal a tile first, the mines are placed then"),
             InvalidErr::InvalidSize => write!(f, "Invalid size"),
+            InvalidErr::TooManyMines => write!(f, "The mines must leave at least one safe tile"),
             InvalidErr::InvalidCoordinate(coordinate_err) => write!(f, "Invalid coordinate: {}", coordinate_err),
+            InvalidErr::UndoDisabled => write!(f, "Undo is not allowed in this game"),
+            InvalidErr::NothingToUndo => write!(f, "Nothing to undo"),
+            InvalidErr::NothingToRedo => write!(f, "Nothing to redo"),
         }
     }
 }
@@ -52,30 +81,174 @@ impl fmt::Display for CoordinateErr {
     }
 }
 
-// TODO: a lot of unwrapping happening here
-pub fn start_game() -> Game {
-    println!("Let's play minesweeper game!");
+impl fmt::Display for Hint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (x, y) = (self.coordinate.x, self.coordinate.y);
+
+        match self.verdict {
+            Verdict::Safe => write!(f, "({},{}) is safe: {}", x, y, self.reason),
+            Verdict::Mine => write!(f, "({},{}) is a mine: {}", x, y, self.reason),
+            Verdict::Guess(probability) => write!(f, "({},{}) is the safest guess, with a {:.0}% chance of a mine: {}",
+                x, y, probability * 100.0, self.reason),
+        }
+    }
+}
 
-    let num_players = get_num_players().unwrap();
+impl fmt::Display for LoadErr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LoadErr::Io(io_err) => write!(f, "Could not read the saved game: {}", io_err),
+            LoadErr::Format(json_err) => write!(f, "Not a saved game: {}", json_err),
+            LoadErr::UnsupportedVersion(version) => write!(f, "Saved game version {} is not supported", version),
+            LoadErr::Inconsistent(reason) => write!(f, "Saved game is corrupted: {}", reason),
+        }
+    }
+}
 
-    let players: Vec<Player> = (0..num_players).into_iter()
-      .map(|_| Player::new(get_name()))
-      .collect();
+// Builds a game from the command-line settings, asking for whatever is missing, and the bots in it
+// A fresh random seed is drawn when none is given, so every game can be reproduced
+pub fn start_game(setup: &Setup) -> io::Result<(Game, Bots)> {
+    println!("Let's play minesweeper game!");
 
-    let (h_size, v_size) = get_board_size().unwrap();
-    let game_level = get_difficulty().unwrap();
+    let num_players = match setup.num_players {
+        Some(num_players) => num_players,
+        None => get_num_players()?,
+    };
+
+    // the bots fill the last seats
+    let num_humans = num_players.saturating_sub(setup.bots.len() as u32);
+    let names = (0..num_humans)
+      .map(|i| match &setup.name {
+          Some(name) if i == 0 => Ok(name.clone()),
+          _ => get_name(),
+      })
+      .collect::<io::Result<Vec<String>>>()?;
+
+    // turns go by id, so the people join first
+    let mut players: Vec<Player> = names.into_iter().map(Player::new).collect();
+    let mut bots = Bots::new();
+    for (i, strategy) in setup.bots.iter().enumerate() {
+        let bot = Player::new(format!("{:?} bot {}", strategy, i + 1));
+        bots.insert(bot.id, *strategy);
+        players.push(bot);
+    }
 
-    let mut game = Game::new(h_size, v_size, game_level);
-    
-    game = players.into_iter()
-      .fold(game,|game, player| {
-        game.add_player(player)
-      });
+    let game = players.into_iter()
+      .fold(new_game(setup, num_players)?, |game, player| game.add_player(player));
     
-    game.board.print();
+    game.view().print();
     
-    println!("number of mines: {}\n", game.board.num_mines());
-    game
+    println!("number of mines: {}", game.board.num_mines());
+    println!("seed: {}\n", game.seed());
+    Ok((game, bots))
+}
+
+// A game for `num_players` who have not joined yet, e.g. on a server
+pub fn new_game(setup: &Setup, num_players: u32) -> io::Result<Game> {
+    let mut game_level = match setup.difficulty {
+        Some(difficulty) => difficulty,
+        None => get_difficulty()?,
+    };
+
+    // a classic preset comes with its own board
+    let (h_size, v_size) = match (game_level.board_size(), setup.h_size, setup.v_size) {
+        (Some(board_size), _, _) => board_size,
+        (None, Some(h_size), Some(v_size)) => (h_size, v_size),
+        (None, Some(h_size), None) => (h_size, get_dimension("height")?),
+        (None, None, Some(v_size)) => (get_dimension("width")?, v_size),
+        (None, None, None) => get_board_size()?,
+    };
+
+    while let Err(invalid_err) = validate_mine_count(h_size, v_size, game_level) {
+        println!("{} on a {}x{} board", invalid_err, h_size, v_size);
+        game_level = get_difficulty()?;
+    }
+
+    let first_move = match setup.first_move {
+        Some(first_move) => first_move,
+        None => get_board_kind()?,
+    };
+
+    // a single player always plays by the classic rules
+    let rules = match setup.rules {
+        _ if num_players <= 1 => Rules::Classic,
+        Some(rules) => rules,
+        None => get_rules()?,
+    };
+
+    let seed = setup.seed.unwrap_or_else(rand::random);
+    // mines are placed on the first move so that it always opens an area
+    let mut game = Game::from_board(Board::new_deferred(h_size, v_size, game_level, seed, first_move))
+      .with_rules(rules);
+
+    if num_players > 1 {
+        let mode = match setup.mode {
+            Some(mode) => mode,
+            None => get_mode()?,
+        };
+        let turn_order = match setup.turn_order {
+            Some(turn_order) => turn_order,
+            None => get_turn_order()?,
+        };
+        let extra_turn = match setup.extra_turn {
+            Some(extra_turn) => extra_turn,
+            None => get_extra_turn()?,
+        };
+
+        game = game
+          .with_mode(mode)
+          .with_turn_order(turn_order, extra_turn);
+    }
+
+    Ok(game)
+}
+
+// Resumes a game saved with the `save` command
+pub fn load_game(path: &Path) -> Result<Game, LoadErr> {
+    let game = Game::load(path)?;
+
+    println!("Welcome back!");
+    game.view().print();
+
+    println!("number of mines: {}", game.board.num_mines());
+    println!("seed: {}\n", game.seed());
+    print_scores(&game);
+    Ok(game)
+}
+
+// Prints the board after every move of a recorded game
+pub fn show_replay(path: &Path) -> Result<(), LoadErr> {
+    let log = ReplayLog::load(path)?;
+
+    for (step, game) in log.replay()?.enumerate() {
+        match step.checked_sub(1).map(|i| &log.moves[i].player_action) {
+            Some(player_action) => println!("move {}: {} {:?} {},{}", step, game.get_player(&player_action.player_id).name,
+                player_action.action, player_action.coordinate.x, player_action.coordinate.y),
+            None => println!("start (seed: {})", game.seed()),
+        }
+
+        // with parallel boards, the board of the player who just moved
+        match step.checked_sub(1).map(|i| &log.moves[i].player_action) {
+            Some(player_action) => game.player_view(&player_action.player_id).print(),
+            None => game.view().print(),
+        }
+        print_scores(&game);
+
+        if game.status != GameStatus::Continue {
+            announce_winners(&game);
+        }
+    }
+
+    Ok(())
+}
+
+// In training mode, hidden tiles show the chance that they are mines
+pub fn print_view(view: &PlayerView, training: bool) {
+    if training {
+        view.print_overlay(&mine_probabilities(view));
+    } else {
+        view.print();
+    }
 }
 
 pub fn print_scores(game: &Game) {
@@ -85,21 +258,126 @@ pub fn print_scores(game: &Game) {
     println!();
 }
 
+// With parallel boards, when everyone cleared their board and how many mistakes they made
+pub fn print_ranking(game: &Game) {
+    for (place, player) in game.ranking().into_iter().enumerate() {
+        let progress = game.progress(&player.id);
+        let result = match progress.completed_at {
+            Some(move_number) => format!("cleared at move {}", move_number),
+            None if game.is_eliminated(&player.id) => "out".to_string(),
+            None => "not cleared".to_string(),
+        };
+
+        println!("{}. {}: {} points, {}, {} mistakes in {} moves", place + 1, player.name, player.points, result, progress.mistakes, progress.moves);
+    }
+
+    println!();
+}
+
 // Prints the end of game message
 pub fn announce_winners(game: &Game) {
+    if game.mode == Mode::ParallelBoards {
+        print_ranking(game);
+    }
+
+    if let GameStatus::Lost(player_id, coordinate) = game.status {
+        println!("{} revealed a mine at {},{}. Game over!", game.get_player(&player_id).name, coordinate.x, coordinate.y);
+        return;
+    }
+
     game.get_winners().into_iter()
       .for_each(|winner| print!("{} ", winner.name));
 
     println!("won!");    
 }
 
+// What a player can enter at the coordinate prompt
+#[derive(Debug, PartialEq)]
+pub enum Command {
+    Coordinate(Coordinate),
+    Undo,
+    Redo,
+    Hint,
+    Save(PathBuf),
+}
+
+// Applies an undo or redo command to both the history and the replay log
+pub fn undo_or_redo(history: &GameHistory, log: &mut ReplayLog, command: &Command, player_id: &PlayerId) -> Result<GameHistory, InvalidErr> {
+    match command {
+        Command::Undo => history.undo(player_id).inspect(|_| log.undo(player_id, history.policy.penalty())),
+        Command::Redo => history.redo().inspect(|_| log.redo()),
+        Command::Coordinate(_) | Command::Hint | Command::Save(_) => Err(InvalidErr::InvalidAction),
+    }
+}
+
+// Saves the game for `--load`, and tells the player how it went
+pub fn save_game(game: &Game, path: &Path) {
+    match game.save(path) {
+        Ok(()) => println!("Game saved to {}. Enter a coordinate to keep playing.", path.display()),
+        Err(io_err) => println!("Could not save the game: {}", io_err),
+    }
+}
+
+// A person at this keyboard, answering prompts
+pub struct Human;
+
+impl PlayerController for Human {
+    fn choose(&mut self, view: &PlayerView, player: &Player) -> io::Result<Choice> {
+        Ok(match get_coordinate(view, player)? {
+            Command::Coordinate(coordinate) => Choice::Play(get_action(view, player, coordinate)?),
+            Command::Undo => Choice::Undo,
+            Command::Redo => Choice::Redo,
+            Command::Hint => Choice::Hint,
+            Command::Save(path) => Choice::Save(path),
+        })
+    }
+}
+
+// The hint, with what it costs in a scored game
+pub fn hint_message(game: &Game, hint: &Hint) -> String {
+    if game.is_scored() {
+        format!("Hint (-{} points): {}", HINT_COST, hint)
+    } else {
+        format!("Hint: {}", hint)
+    }
+}
+
+// Shows the player a hint and returns the move that pays for it, if a tile is still hidden
+pub fn take_hint(game: &Game, player_id: &PlayerId) -> Option<PlayerAction> {
+    let Some(hint) = hint(&game.player_view(player_id)) else {
+        println!("No hidden tiles are left");
+        return None;
+    };
+
+    println!("{}", hint_message(game, &hint));
+    Some(PlayerAction{ player_id: *player_id, coordinate: hint.coordinate, action: Action::Hint })
+}
+
+// A no-guess board that could not be built places its mines anyway, and the player should know
+pub fn guess_warning(before: &Game, after: &Game, player_id: &PlayerId) -> Option<&'static str> {
+    let placed_now = !before.player_board(player_id).mines_placed() && after.player_board(player_id).mines_placed();
+
+    (placed_now && after.player_board(player_id).needs_guess())
+        .then_some("No board could be built that is solvable without guessing, so this one may need a guess")
+}
+
 // Prompts a message to get a valid coordinate from player
-pub fn get_coordinate(game: &Game, player: &Player) -> io::Result<Coordinate> {
-    println!("{}, enter a coordinate: x,y", player.name);
+pub fn get_coordinate(view: &PlayerView, player: &Player) -> io::Result<Command> {
+    println!("{}, enter a coordinate: x,y (or save <file>, undo, redo, hint)", player.name);
     // The loop continues until one branch hits return Ok(valid_coord)
     loop {
-        let mut player_input = String::new();
-        io::stdin().read_line(&mut player_input)?;
+        let player_input = read_input()?;
+
+        if let Some(path) = player_input.trim().strip_prefix("save ") {
+            return Ok(Command::Save(PathBuf::from(path.trim())));
+        }
+
+        match player_input.trim() {
+            "undo" => return Ok(Command::Undo),
+            "redo" => return Ok(Command::Redo),
+            "hint" => return Ok(Command::Hint),
+            _ => {}
+        }
 
         let parsed_coord = match parse_coordinate(&player_input) {
             Ok(coord) => coord,
@@ -108,8 +386,8 @@ pub fn get_coordinate(game: &Game, player: &Player) -> io::Result<Coordinate> {
             }
         };
          
-        match validate_coordinate(&game.board, &parsed_coord, player) {
-            Ok(coord) => return Ok(coord),
+        match validate_coordinate(view, &parsed_coord, player) {
+            Ok(coord) => return Ok(Command::Coordinate(coord)),
             Err(e) => { 
                 try_again!(e);
             }
@@ -122,7 +400,7 @@ pub fn get_coordinate(game: &Game, player: &Player) -> io::Result<Coordinate> {
 //   2,3 is ok
 //   4,k is error - not number
 //   1,2,3 is error - bad format
-pub fn parse_coordinate(player_input: &String) -> Result<Coordinate, ParseErr> {
+pub fn parse_coordinate(player_input: &str) -> Result<Coordinate, ParseErr> {
     let chars: Vec<&str> = player_input.trim().split(',').collect();
 
     match chars.len() {
@@ -142,12 +420,11 @@ pub fn parse_coordinate(player_input: &String) -> Result<Coordinate, ParseErr> {
     }
 }
 
-pub fn get_action(game: &Game, player: &Player, coordinate: Coordinate) -> io::Result<PlayerAction> {
-    println!("Enter an action: Flag or Reveal");
+pub fn get_action(view: &PlayerView, player: &Player, coordinate: Coordinate) -> io::Result<PlayerAction> {
+    println!("Enter an action: Flag, Unflag, Reveal or Chord");
 
     loop {
-        let mut player_input = String::new();
-        io::stdin().read_line(&mut player_input)?;
+        let player_input = read_input()?;
         
         let parsed_action = match parse_action(player_input) {
             Ok(action) => action,
@@ -158,7 +435,7 @@ pub fn get_action(game: &Game, player: &Player, coordinate: Coordinate) -> io::R
 
         let player_action = PlayerAction{ player_id: player.id, coordinate, action: parsed_action };
 
-        match validate_action(&game, player_action, &coordinate) {
+        match validate_action(view, player_action, &coordinate) {
                 Ok(player_action) => return Ok(player_action),
                 Err(invalid_err) => { 
                     try_again!(invalid_err) 
@@ -171,24 +448,24 @@ pub fn parse_action(player_input: String) -> Result<Action, ParseErr> {
     match player_input.trim() {
         "Reveal" => Ok(Action::Reveal),
         "Flag" => Ok(Action::Flag),
-        // "Unflag" => Ok(Action::Unflag),
+        "Unflag" => Ok(Action::Unflag),
+        "Chord" => Ok(Action::Chord),
         _ => Err(ParseErr::ParsingFailed)
     }
 }
 
-const MAX_NUM_PLAYERS: u32 = 5;
+pub const MAX_NUM_PLAYERS: u32 = 5;
 
 pub fn get_num_players() -> io::Result<u32> {
     println!("How many players?");
 
     loop {
-        let mut player_input = String::new();
-        io::stdin().read_line(&mut player_input)?;
+        let player_input = read_input()?;
 
         let num_players = player_input.trim().parse::<u32>();
 
         match num_players {
-            Ok(num_players) => if num_players > MAX_NUM_PLAYERS {
+            Ok(num_players) => if num_players == 0 || num_players > MAX_NUM_PLAYERS {
                 try_again!(InvalidErr::InvalidSize);
             } else {
                 return Ok(num_players)
@@ -206,8 +483,7 @@ pub fn get_board_size() -> io::Result<BoardSize> {
     println!("Enter your board size: n,n");
 
     loop {
-        let mut player_input = String::new();
-        io::stdin().read_line(&mut player_input)?;
+        let player_input = read_input()?;
 
         let parsed_board_size = match parse_board_size(player_input) {
             Ok(board_size) => board_size,
@@ -245,23 +521,42 @@ pub fn parse_board_size(player_input: String) -> Result<BoardSize, ParseErr> {
     }
 }
 
-pub fn get_name() -> String {
+pub fn get_name() -> io::Result<String> {
     println!("Enter your name");
 
-    let mut player_input = String::new();
-    io::stdin()
-        .read_line(&mut player_input)
-        .expect("Failed to read name");
-    
-    player_input.trim().to_string()
+    Ok(read_input()?.trim().to_string())
+}
+
+pub fn get_replay_file() -> io::Result<String> {
+    println!("Enter the replay file");
+
+    Ok(read_input()?.trim().to_string())
+}
+
+// Asks for one side of the board when the other was given on the command line
+pub fn get_dimension(side: &str) -> io::Result<u32> {
+    println!("Enter the board {}", side);
+
+    loop {
+        let player_input = read_input()?;
+
+        match player_input.trim().parse::<u32>() {
+            Ok(0) => try_again!(ParseErr::NegativeNum),
+            Ok(size) => match validate_board_size(size, size) {
+                Ok(_) => return Ok(size),
+                Err(size_err) => try_again!(size_err),
+            },
+            Err(_) => try_again!(ParseErr::NotNum),
+        }
+    }
 }
 
 pub fn get_difficulty() -> io::Result<Difficulty> {
-    println!("Enter the level of difficulty: Easy, Medium, or Hard");
+    println!("Enter the level of difficulty: Easy, Medium or Hard, a classic Beginner (9x9), Intermediate (16x16) or Expert (30x16),");
+    println!("a number of mines, or a density such as 0.2");
 
     loop {
-        let mut player_input = String::new();
-        io::stdin().read_line(&mut player_input)?;
+        let player_input = read_input()?;
         
         match parse_difficulty(player_input) {
             Ok(difficulty) => return Ok(difficulty),
@@ -272,11 +567,163 @@ pub fn get_difficulty() -> io::Result<Difficulty> {
     }
 }
 
+pub fn get_board_kind() -> io::Result<FirstMove> {
+    println!("Enter the kind of board: Classic, SafeTile or NoGuess");
+
+    loop {
+        let player_input = read_input()?;
+
+        match parse_board_kind(player_input) {
+            Ok(first_move) => return Ok(first_move),
+            Err(parse_err) => {
+                try_again!(parse_err);
+            }
+        }
+    }
+}
+
+// Classic and NoGuess start with an opening; a NoGuess board can also be finished without guessing
+// SafeTile only keeps the first tile itself clear of mines
+pub fn parse_board_kind(player_input: String) -> Result<FirstMove, ParseErr> {
+    match player_input.trim() {
+        "Classic" => Ok(FirstMove::SafeOpening),
+        "SafeTile" => Ok(FirstMove::SafeTile),
+        "NoGuess" => Ok(FirstMove::NoGuess),
+        _ => Err(ParseErr::ParsingFailed)
+    }
+}
+
+pub fn get_rules() -> io::Result<Rules> {
+    println!("Enter the rules: Classic, Elimination or PointsOnly");
+
+    loop {
+        let player_input = read_input()?;
+
+        match parse_rules(player_input) {
+            Ok(rules) => return Ok(rules),
+            Err(parse_err) => {
+                try_again!(parse_err);
+            }
+        }
+    }
+}
+
+// e.g. safe,probability
+pub fn parse_bots(player_input: &str) -> Result<Vec<Strategy>, ParseErr> {
+    player_input.split(',')
+      .map(|strategy| match strategy.trim() {
+          "random" => Ok(Strategy::Random),
+          "safe" => Ok(Strategy::Safe),
+          "probability" => Ok(Strategy::Probability),
+          _ => Err(ParseErr::ParsingFailed)
+      })
+      .collect()
+}
+
+pub fn parse_rules(player_input: String) -> Result<Rules, ParseErr> {
+    match player_input.trim() {
+        "Classic" => Ok(Rules::Classic),
+        "Elimination" => Ok(Rules::Elimination),
+        "PointsOnly" => Ok(Rules::PointsOnly),
+        _ => Err(ParseErr::ParsingFailed)
+    }
+}
+
+pub fn get_mode() -> io::Result<Mode> {
+    println!("Do the players share one board? Shared or Parallel");
+
+    loop {
+        let player_input = read_input()?;
+
+        match parse_mode(player_input) {
+            Ok(mode) => return Ok(mode),
+            Err(parse_err) => {
+                try_again!(parse_err);
+            }
+        }
+    }
+}
+
+pub fn parse_mode(player_input: String) -> Result<Mode, ParseErr> {
+    match player_input.trim() {
+        "Shared" => Ok(Mode::SharedBoard),
+        "Parallel" => Ok(Mode::ParallelBoards),
+        _ => Err(ParseErr::ParsingFailed)
+    }
+}
+
+pub fn get_turn_order() -> io::Result<TurnOrder> {
+    println!("Enter the turn order: RoundRobin, Shuffled or Race");
+
+    loop {
+        let player_input = read_input()?;
+
+        match parse_turn_order(player_input) {
+            Ok(turn_order) => return Ok(turn_order),
+            Err(parse_err) => {
+                try_again!(parse_err);
+            }
+        }
+    }
+}
+
+pub fn parse_turn_order(player_input: String) -> Result<TurnOrder, ParseErr> {
+    match player_input.trim() {
+        "RoundRobin" => Ok(TurnOrder::RoundRobin),
+        "Shuffled" => Ok(TurnOrder::ShuffledRounds),
+        "Race" => Ok(TurnOrder::Simultaneous),
+        _ => Err(ParseErr::ParsingFailed)
+    }
+}
+
+pub fn get_extra_turn() -> io::Result<bool> {
+    println!("Does flagging a mine earn an extra turn? yes or no");
+
+    loop {
+        let player_input = read_input()?;
+
+        match parse_yes_no(player_input) {
+            Ok(extra_turn) => return Ok(extra_turn),
+            Err(parse_err) => {
+                try_again!(parse_err);
+            }
+        }
+    }
+}
+
+pub fn parse_yes_no(player_input: String) -> Result<bool, ParseErr> {
+    match player_input.trim() {
+        "yes" => Ok(true),
+        "no" => Ok(false),
+        _ => Err(ParseErr::ParsingFailed)
+    }
+}
+
+// Parses the value given to `--seed`
+pub fn parse_seed(input: &str) -> Result<u64, ParseErr> {
+    input.trim().parse::<u64>().map_err(|_| ParseErr::NotNum)
+}
+
+// A whole number is a number of mines and a fraction a density
+// For example,
+//   Expert
+//   40 is Custom { mines: 40 }
+//   0.2 is Density(0.2)
 pub fn parse_difficulty(player_input: String) -> Result<Difficulty, ParseErr> {
     match player_input.trim() {
         "Easy" => Ok(Difficulty::Easy),
         "Medium" => Ok(Difficulty::Medium),
         "Hard" => Ok(Difficulty::Hard),
-        _ => Err(ParseErr::ParsingFailed)
+        "Beginner" => Ok(Difficulty::Beginner),
+        "Intermediate" => Ok(Difficulty::Intermediate),
+        "Expert" => Ok(Difficulty::Expert),
+        number if number.contains('.') => match number.parse::<f32>() {
+            Ok(density) if density.is_finite() && density >= 0.0 => Ok(Difficulty::Density(density)),
+            Ok(_) => Err(ParseErr::NegativeNum),
+            Err(_) => Err(ParseErr::ParsingFailed),
+        },
+        number => number.parse::<u32>()
+            .map(|mines| Difficulty::Custom { mines })
+            .map_err(|_| ParseErr::ParsingFailed),
     }
 }
@@ -0,0 +1,344 @@
+// Full-screen terminal interface for a single player
+//
+// Keys: arrows or hjkl move the cursor, space reveals, f flags (or takes back your flag), c chords,
+// ? shows a hint, u/r undo and redo, s saves, q quits.
+
+use std::collections::HashMap;
+use std::io::{self, Write};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use crossterm::cursor::{Hide, MoveTo, Show};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::style::{Attribute, Color, Print, ResetColor, SetAttribute, SetForegroundColor};
+use crossterm::terminal::{self, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen};
+use crossterm::{execute, queue};
+
+use crate::core::board::{Coordinate, Tile, TileStatus};
+use crate::core::game::{Game, GameStatus};
+use crate::core::hint::hint;
+use crate::core::history::GameHistory;
+use crate::core::player::{Action, PlayerAction, PlayerId};
+use crate::core::probability::mine_probabilities;
+use crate::core::replay::ReplayLog;
+use crate::core::validation::{validate_action, validate_coordinate};
+use crate::core::view::PlayerView;
+use crate::single_player::text_ui::{guess_warning, hint_message, print_view, undo_or_redo, Command};
+
+const SAVE_FILE: &str = "minesweeper.json";
+const HELP: &str = "arrows/hjkl move  space reveal  f flag  c chord  ? hint  u undo  r redo  s save  q quit";
+
+#[derive(Debug, PartialEq)]
+enum TuiCommand {
+    Move(i32, i32),
+    Reveal,
+    Flag,
+    Chord,
+    Hint,
+    Undo,
+    Redo,
+    Save,
+    Quit,
+}
+
+fn command(key: KeyEvent) -> Option<TuiCommand> {
+    if key.modifiers.contains(KeyModifiers::CONTROL) {
+        // raw mode turns Ctrl-C into a plain key press
+        return (key.code == KeyCode::Char('c')).then_some(TuiCommand::Quit);
+    }
+
+    match key.code {
+        KeyCode::Left | KeyCode::Char('h') => Some(TuiCommand::Move(-1, 0)),
+        KeyCode::Down | KeyCode::Char('j') => Some(TuiCommand::Move(0, 1)),
+        KeyCode::Up | KeyCode::Char('k') => Some(TuiCommand::Move(0, -1)),
+        KeyCode::Right | KeyCode::Char('l') => Some(TuiCommand::Move(1, 0)),
+        KeyCode::Char(' ') => Some(TuiCommand::Reveal),
+        KeyCode::Char('f') => Some(TuiCommand::Flag),
+        KeyCode::Char('c') => Some(TuiCommand::Chord),
+        KeyCode::Char('?') => Some(TuiCommand::Hint),
+        KeyCode::Char('u') => Some(TuiCommand::Undo),
+        KeyCode::Char('r') => Some(TuiCommand::Redo),
+        KeyCode::Char('s') => Some(TuiCommand::Save),
+        KeyCode::Char('q') | KeyCode::Esc => Some(TuiCommand::Quit),
+        _ => None,
+    }
+}
+
+// The cursor stops at the edges of the board
+fn move_cursor(view: &PlayerView, cursor: Coordinate, dx: i32, dy: i32) -> Coordinate {
+    let x = (cursor.x as i32 + dx).clamp(0, view.h_size as i32 - 1);
+    let y = (cursor.y as i32 + dy).clamp(0, view.v_size as i32 - 1);
+
+    Coordinate{ x: x as u32, y: y as u32 }
+}
+
+// f on a player's own flag takes it back
+fn flag_action(view: &PlayerView, coordinate: &Coordinate, player_id: PlayerId) -> Action {
+    match view.get_tile(coordinate) {
+        TileStatus::Flagged(flagged_by) if *flagged_by == player_id => Action::Unflag,
+        _ => Action::Flag,
+    }
+}
+
+fn hint_color(n: i8) -> Color {
+    match n {
+        1 => Color::Blue,
+        2 => Color::Green,
+        3 => Color::Red,
+        4 => Color::DarkBlue,
+        5 => Color::DarkRed,
+        6 => Color::Cyan,
+        7 => Color::Magenta,
+        _ => Color::Grey,
+    }
+}
+
+// In training mode, a hidden tile shows the percent chance that it is a mine
+fn tile_glyph(tile_status: &TileStatus, probability: Option<f64>) -> (String, Color) {
+    match tile_status {
+        TileStatus::Hidden => match probability {
+            Some(p) => (format!("{:>3.0}", p * 100.0), Color::DarkGrey),
+            None => (" ? ".to_string(), Color::DarkGrey),
+        },
+        TileStatus::Flagged(_) => (" ! ".to_string(), Color::Yellow),
+        TileStatus::Revealed(Tile::Hint(0)) => ("   ".to_string(), Color::Reset),
+        TileStatus::Revealed(Tile::Hint(n)) => (format!(" {} ", n), hint_color(*n)),
+        TileStatus::Revealed(Tile::Mine) => (" * ".to_string(), Color::Red),
+    }
+}
+
+fn status_line(game: &Game, player_id: PlayerId, elapsed: Duration) -> String {
+    let view = game.player_view(&player_id);
+    let mines_left = view.num_mines as i64 - view.num_known_mines() as i64;
+    let seconds = elapsed.as_secs();
+    let player = game.get_player(&player_id);
+
+    format!("Mines left: {}   Time: {:02}:{:02}   {}: {} points", mines_left, seconds / 60, seconds % 60, player.name, player.points)
+}
+
+// Mine probabilities for the training overlay, or none
+fn overlay(game: &Game, player_id: PlayerId, training: bool) -> HashMap<Coordinate, f64> {
+    if training { mine_probabilities(&game.player_view(&player_id)) } else { HashMap::new() }
+}
+
+fn render(out: &mut impl Write, game: &Game, player_id: PlayerId, cursor: Coordinate, elapsed: Duration, message: &str,
+    probabilities: &HashMap<Coordinate, f64>) -> io::Result<()> {
+    let view = game.player_view(&player_id);
+
+    queue!(out, Clear(ClearType::All), MoveTo(0, 0), Print(status_line(game, player_id, elapsed)))?;
+
+    for y in 0..view.v_size {
+        queue!(out, MoveTo(0, y as u16 + 2))?;
+
+        for x in 0..view.h_size {
+            let coordinate = Coordinate{ x, y };
+            let (glyph, color) = tile_glyph(view.get_tile(&coordinate), probabilities.get(&coordinate).copied());
+
+            if coordinate == cursor {
+                queue!(out, SetAttribute(Attribute::Reverse))?;
+            }
+            queue!(out, SetForegroundColor(color), Print(glyph), ResetColor, SetAttribute(Attribute::Reset))?;
+        }
+    }
+
+    let bottom = view.v_size as u16 + 3;
+    queue!(out, MoveTo(0, bottom), Print(message), MoveTo(0, bottom + 1), Print(HELP))?;
+    out.flush()
+}
+
+// Puts the terminal back however play ends, including a panic
+struct RawScreen;
+
+impl RawScreen {
+    fn enter() -> io::Result<RawScreen> {
+        terminal::enable_raw_mode()?;
+        execute!(io::stdout(), EnterAlternateScreen, Hide)?;
+        Ok(RawScreen)
+    }
+}
+
+impl Drop for RawScreen {
+    fn drop(&mut self) {
+        let _ = execute!(io::stdout(), Show, LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+// Plays until the game is over or the player quits, recording moves like the prompt loop
+// With `training`, hidden tiles show the chance that they are mines
+pub fn play(mut history: GameHistory, log: &mut ReplayLog, record: Option<&Path>, player_id: PlayerId, training: bool) -> io::Result<GameHistory> {
+    let screen = RawScreen::enter()?;
+    let mut stdout = io::stdout();
+
+    let started = Instant::now();
+    let mut finished: Option<Duration> = None;
+    let view = history.game().player_view(&player_id);
+    let mut cursor = Coordinate{ x: view.h_size / 2, y: view.v_size / 2 };
+    let mut message = String::new();
+    let mut probabilities = overlay(history.game(), player_id, training);
+
+    loop {
+        let elapsed = finished.unwrap_or_else(|| started.elapsed());
+        render(&mut stdout, history.game(), player_id, cursor, elapsed, &message, &probabilities)?;
+
+        // redraw every half second to keep the timer running
+        if !event::poll(Duration::from_millis(500))? {
+            continue;
+        }
+        let Event::Key(key) = event::read()? else { continue };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+
+        // once the game is over, any key leaves
+        if finished.is_some() {
+            break;
+        }
+
+        let view = history.game().player_view(&player_id);
+        let mut shown_hint = None;
+        let action = match command(key) {
+            Some(TuiCommand::Move(dx, dy)) => {
+                cursor = move_cursor(&view, cursor, dx, dy);
+                continue;
+            },
+            Some(TuiCommand::Reveal) => Action::Reveal,
+            Some(TuiCommand::Flag) => flag_action(&view, &cursor, player_id),
+            Some(TuiCommand::Chord) => Action::Chord,
+            // the cursor jumps to the tile the hint is about
+            Some(TuiCommand::Hint) => match hint(&view) {
+                Some(hint) => {
+                    cursor = hint.coordinate;
+                    shown_hint = Some(hint_message(history.game(), &hint));
+                    Action::Hint
+                },
+                None => continue,
+            },
+            Some(command @ (TuiCommand::Undo | TuiCommand::Redo)) => {
+                let command = if command == TuiCommand::Undo { Command::Undo } else { Command::Redo };
+                message = match undo_or_redo(&history, log, &command, &player_id) {
+                    Ok(updated_history) => {
+                        history = updated_history;
+                        probabilities = overlay(history.game(), player_id, training);
+                        String::new()
+                    },
+                    Err(invalid_err) => invalid_err.to_string(),
+                };
+                continue;
+            },
+            Some(TuiCommand::Save) => {
+                message = match history.game().save(Path::new(SAVE_FILE)) {
+                    Ok(()) => format!("Game saved to {}", SAVE_FILE),
+                    Err(io_err) => format!("Could not save the game: {}", io_err),
+                };
+                continue;
+            },
+            Some(TuiCommand::Quit) => break,
+            None => continue,
+        };
+
+        let game = history.game();
+        let player_action = validate_coordinate(&view, &cursor, game.get_player(&player_id))
+            .and_then(|coordinate| validate_action(&view, PlayerAction{ player_id, coordinate, action }, &coordinate));
+
+        let updated_history = player_action.and_then(|player_action| history.update(&player_action).map(|h| (player_action, h)));
+        message = match updated_history {
+            Ok((player_action, updated_history)) => {
+                let warning = guess_warning(history.game(), updated_history.game(), &player_id);
+                history = updated_history;
+                probabilities = overlay(history.game(), player_id, training);
+                if let Some(path) = record {
+                    log.record(&player_action);
+                    log.save(path)?;
+                }
+                shown_hint.or(warning.map(str::to_string)).unwrap_or_default()
+            },
+            Err(invalid_err) => invalid_err.to_string(),
+        };
+
+        if history.game().status != GameStatus::Continue {
+            finished = Some(started.elapsed());
+            message = match history.game().status {
+                GameStatus::Won => "You won! Press any key.".to_string(),
+                _ => "Boom! Press any key.".to_string(),
+            };
+        }
+    }
+
+    drop(screen);
+
+    // leave the final board in the terminal
+    print_view(&history.game().player_view(&player_id), training);
+    println!("{}", status_line(history.game(), player_id, finished.unwrap_or_else(|| started.elapsed())));
+
+    Ok(history)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+    use crate::core::board::Board;
+    use crate::core::player::Player;
+
+    fn game() -> Game {
+        Game::from_board(Board::new_test(3, 3, HashSet::from([Coordinate{ x: 0, y: 0 }])))
+            .add_player(Player::new_with_id(1, "hyeyoung"))
+    }
+
+    #[test]
+    fn keys() {
+        let key = |code| KeyEvent::new(code, KeyModifiers::NONE);
+
+        assert_eq!(command(key(KeyCode::Char('h'))), Some(TuiCommand::Move(-1, 0)));
+        assert_eq!(command(key(KeyCode::Down)), Some(TuiCommand::Move(0, 1)));
+        assert_eq!(command(key(KeyCode::Char(' '))), Some(TuiCommand::Reveal));
+        assert_eq!(command(key(KeyCode::Char('c'))), Some(TuiCommand::Chord));
+        assert_eq!(command(KeyEvent::new(KeyCode::Char('?'), KeyModifiers::SHIFT)), Some(TuiCommand::Hint));
+        assert_eq!(command(KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL)), Some(TuiCommand::Quit));
+        assert_eq!(command(key(KeyCode::Char('x'))), None);
+    }
+
+    #[test]
+    fn cursor_stays_on_board() {
+        let view = game().view();
+
+        assert_eq!(move_cursor(&view, Coordinate{ x: 0, y: 0 }, -1, 0), Coordinate{ x: 0, y: 0 });
+        assert_eq!(move_cursor(&view, Coordinate{ x: 2, y: 1 }, 1, 1), Coordinate{ x: 2, y: 2 });
+        assert_eq!(move_cursor(&view, Coordinate{ x: 1, y: 1 }, 0, -1), Coordinate{ x: 1, y: 0 });
+    }
+
+    #[test]
+    fn f_toggles_own_flag() {
+        let mine = Coordinate{ x: 0, y: 0 };
+        let game = game().update(&PlayerAction{ player_id: 1, coordinate: mine, action: Action::Flag }).unwrap();
+
+        assert_eq!(flag_action(&game.view(), &mine, 1), Action::Unflag);
+        assert_eq!(flag_action(&game.view(), &Coordinate{ x: 1, y: 1 }, 1), Action::Flag);
+    }
+
+    #[test]
+    fn status_counts_flags() {
+        let game = game().update(&PlayerAction{ player_id: 1, coordinate: Coordinate{ x: 0, y: 0 }, action: Action::Flag }).unwrap();
+
+        assert_eq!(status_line(&game, 1, Duration::from_secs(75)), "Mines left: 0   Time: 01:15   hyeyoung: 2 points");
+    }
+
+    #[test]
+    fn renders_without_a_terminal() {
+        let mut buffer = Vec::new();
+        render(&mut buffer, &game(), 1, Coordinate{ x: 1, y: 1 }, Duration::ZERO, "", &HashMap::new()).unwrap();
+
+        assert!(String::from_utf8(buffer).unwrap().contains("Mines left: 1"));
+    }
+
+    #[test]
+    fn training_shows_percentages() {
+        assert_eq!(tile_glyph(&TileStatus::Hidden, Some(0.25)).0, " 25");
+        assert_eq!(tile_glyph(&TileStatus::Hidden, Some(1.0)).0, "100");
+        assert_eq!(tile_glyph(&TileStatus::Hidden, None).0, " ? ");
+        assert!(overlay(&game(), 1, false).is_empty());
+        assert_eq!(overlay(&game(), 1, true).len(), 9);
+    }
+}